@@ -0,0 +1,383 @@
+use std::{fmt, sync::RwLock};
+use crate::{
+    board::{Board, Loc, read_board},
+    types::{MoveData, Movement::*, Space, Space::*}
+};
+
+
+// pieces described in Betza notation, the way fairy chess writes them down:
+// capital letters are leaps ("W" one square orthogonally, "N" the knight's jump),
+// a doubled letter or a number rides that leap along a line ("WW" or "W0" is a rook, "W3" goes up to three),
+// and lower case letters in front narrow it down: "f" forwards, "b" backwards, "l" and "r" to either side,
+// "v" along the file and "s" along the rank, "m" only to move, "c" only to capture,
+// "n" can't jump over anything on the way, "p" has to hop over a piece first, "g" lands just past it,
+// and "i" only on the piece's first move. so "fmWfcF" is a pawn without the double step.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BetzaError {
+    Empty,
+    Modifier(char),
+    Atom(char),
+    // modifiers with nothing after them to apply to.
+    Dangling,
+    // Space::Fairy only has room for so many.
+    TooMany
+}
+
+impl fmt::Display for BetzaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BetzaError::Empty => write!(f, "a piece has to move somehow"),
+            BetzaError::Modifier(c) => write!(f, "'{}' isn't a modifier this understands", c),
+            BetzaError::Atom(c) => write!(f, "'{}' isn't a move this understands", c),
+            BetzaError::Dangling => write!(f, "the modifiers at the end don't go with any move"),
+            BetzaError::TooMany => write!(f, "there's only room for {} made up pieces", SLOTS)
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Hop {
+    No,
+    // like a Xiangqi cannon, anywhere past the piece jumped over.
+    Cannon,
+    // like a grasshopper, only the square straight after it.
+    Grasshopper
+}
+
+// one leap or ride a piece can make, with every direction it goes in spelled out.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Atom {
+    // steps as [sideways, forwards] from the mover's side of the board, right and forwards being positive.
+    steps: Vec<Loc>,
+    // how many steps it can take in a line; 0 is as many as fit.
+    range: isize,
+    moves: bool,
+    captures: bool,
+    lame: bool,
+    hop: Hop,
+    initial: bool
+}
+
+// the basic leaps, as [sideways, forwards].
+fn leap(atom: char) -> Option<Loc> {
+    Some(match atom {
+        'W' => [0, 1],
+        'F' => [1, 1],
+        'D' => [0, 2],
+        'N' => [1, 2],
+        'A' => [2, 2],
+        'H' => [0, 3],
+        'C' | 'L' => [1, 3],
+        'Z' => [2, 3],
+        'G' => [3, 3],
+        _ => {return None;}
+    })
+}
+
+// the shorthands for the usual pieces, as the leaps they're made of and whether they ride them.
+fn expand(atom: char) -> Option<Vec<(char, bool)>> {
+    Some(match atom {
+        'R' => vec![('W', true)],
+        'B' => vec![('F', true)],
+        'Q' => vec![('W', true), ('F', true)],
+        'K' => vec![('W', false), ('F', false)],
+        _ => vec![(leap(atom).map(|_| atom)?, false)]
+    })
+}
+
+// all eight ways round a leap can point, without repeats.
+fn symmetries(step: Loc) -> Vec<Loc> {
+    let [a, b] = step;
+    let mut steps = Vec::new();
+    for [s, f] in [[a, b], [b, a]] {
+        for [ds, df] in [[1, 1], [1, -1], [-1, 1], [-1, -1]] {
+            let turned = [s * ds, f * df];
+            if !steps.contains(&turned) {
+                steps.push(turned);
+            }
+        }
+    }
+    steps
+}
+
+// whether a step goes the way one or two direction letters say.
+// a letter doubled up, like "ff", means the narrow side of an oblique leap.
+fn points(step: Loc, letters: &[char]) -> bool {
+    let [s, f] = step;
+    letters.iter().all(|letter| match letter {
+        'f' => f > 0 && (letters != ['f', 'f'] || f.abs() > s.abs()),
+        'b' => f < 0 && (letters != ['b', 'b'] || f.abs() > s.abs()),
+        'l' => s < 0,
+        'r' => s > 0,
+        'v' => f.abs() > s.abs(),
+        's' => s.abs() > f.abs(),
+        _ => false
+    })
+}
+
+// groups the direction letters the way they're meant: a pair like "fl" is one direction
+// for leaps that aren't straight, and for straight ones each letter is a direction of its own.
+fn directions(letters: &[char], straight: bool) -> Vec<Vec<char>> {
+    let mut groups = Vec::new();
+    let mut i = 0;
+    while i < letters.len() {
+        let pair = letters.get(i + 1).is_some_and(|next| {
+            !straight && matches!(letters[i], 'f' | 'b') && matches!(next, 'f' | 'b' | 'l' | 'r' | 's' | 'v')
+                && (*next == letters[i] || !matches!(next, 'f' | 'b'))
+        });
+        let end = if pair {i + 2} else {i + 1};
+        groups.push(letters[i..end].to_vec());
+        i = end;
+    }
+    groups
+}
+
+pub fn parse(notation: &str) -> Result<Vec<Atom>, BetzaError> {
+
+    let mut atoms = Vec::new();
+    let mut chars = notation.chars().filter(|c| !c.is_whitespace()).peekable();
+    let mut modifiers = Vec::new();
+
+    while let Some(c) = chars.next() {
+        if c.is_ascii_lowercase() {
+            if !"fblrvsmcnpgi".contains(c) {
+                return Err(BetzaError::Modifier(c));
+            }
+            modifiers.push(c);
+            continue;
+        }
+
+        let parts = expand(c).ok_or(BetzaError::Atom(c))?;
+
+        // "WW" rides the same as "W0", and a number gives how far.
+        let mut range = None;
+        if chars.peek() == Some(&c) {
+            chars.next();
+            range = Some(0);
+        }
+        let mut digits = String::new();
+        while let Some(digit) = chars.peek().filter(|c| c.is_ascii_digit()) {
+            digits.push(*digit);
+            chars.next();
+        }
+        if let Ok(n) = digits.parse::<isize>() {
+            range = Some(n);
+        }
+
+        let letters: Vec<char> = modifiers.iter().copied().filter(|c| "fblrvs".contains(*c)).collect();
+        for (atom, rides) in parts {
+            let step = leap(atom).ok_or(BetzaError::Atom(atom))?;
+            let straight = step[0] == 0;
+            let groups = directions(&letters, straight);
+
+            let steps = symmetries(step).into_iter()
+                .filter(|step| groups.is_empty() || groups.iter().any(|group| points(*step, group)))
+                .collect();
+
+            let (moves, captures) = match (modifiers.contains(&'m'), modifiers.contains(&'c')) {
+                (true, false) => (true, false),
+                (false, true) => (false, true),
+                _ => (true, true)
+            };
+
+            atoms.push(Atom {
+                steps,
+                range: range.unwrap_or(if rides {0} else {1}),
+                moves,
+                captures,
+                lame: modifiers.contains(&'n'),
+                hop: match (modifiers.contains(&'p'), modifiers.contains(&'g')) {
+                    (_, true) => Hop::Grasshopper,
+                    (true, false) => Hop::Cannon,
+                    _ => Hop::No
+                },
+                initial: modifiers.contains(&'i')
+            });
+        }
+        modifiers.clear();
+    }
+
+    match (modifiers.is_empty(), atoms.is_empty()) {
+        (false, _) => Err(BetzaError::Dangling),
+        (true, true) => Err(BetzaError::Empty),
+        (true, false) => Ok(atoms)
+    }
+}
+
+
+// a piece some variant made up, and the letter it goes by in a FEN.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FairyPiece {
+    pub letter: char,
+    pub atoms: Vec<Atom>
+}
+
+impl FairyPiece {
+    pub fn new(letter: char, notation: &str) -> Result<FairyPiece, BetzaError> {
+        Ok(FairyPiece { letter: letter.to_ascii_uppercase(), atoms: parse(notation)? })
+    }
+}
+
+// how many made up pieces there can be at once, one for each number Space::Fairy has.
+const SLOTS: usize = u8::MAX as usize + 1;
+
+// every made up piece in use. Space only has room for a number, so pieces are kept here
+// and looked up by where they are in the list. a slot that's been given back is None until it's used again.
+static PIECES: RwLock<Vec<Option<&'static FairyPiece>>> = RwLock::new(Vec::new());
+
+// adds the pieces, giving back the numbers Space::Fairy knows them by. either they all go in or none do.
+pub fn register(new: Vec<FairyPiece>) -> Result<Vec<u8>, BetzaError> {
+    let mut pieces = PIECES.write().unwrap();
+    let free: Vec<usize> = (0..SLOTS).filter(|i| pieces.get(*i).is_none_or(|slot| slot.is_none())).take(new.len()).collect();
+    if free.len() < new.len() {
+        return Err(BetzaError::TooMany);
+    }
+
+    for (i, piece) in free.iter().zip(new) {
+        if *i >= pieces.len() {
+            pieces.resize(*i + 1, None);
+        }
+        pieces[*i] = Some(Box::leak(Box::new(piece)));
+    }
+    Ok(free.into_iter().map(|i| i as u8).collect())
+}
+
+// gives pieces back, once whatever they were made up for turned out not to work, so their numbers can be used again.
+pub fn unregister(indices: &[u8]) {
+    let mut pieces = PIECES.write().unwrap();
+    for index in indices {
+        if let Some(slot) = pieces.get_mut(*index as usize) {
+            *slot = None;
+        }
+    }
+}
+
+pub fn piece(index: u8) -> &'static FairyPiece {
+    PIECES.read().unwrap()[index as usize].expect("a made up piece that's on the board is still registered")
+}
+
+// whether any pieces have been made up, so check detection can skip looking for them otherwise.
+pub fn any() -> bool {
+    PIECES.read().unwrap().iter().any(Option::is_some)
+}
+
+
+// turns a [sideways, forwards] step into a board step for the given side.
+// white moves up the screen, towards y = 1, and black is the same turned all the way around.
+fn orient(step: Loc, is_white: bool) -> Loc {
+    match is_white {
+        true => [step[0], -step[1]],
+        false => [-step[0], step[1]]
+    }
+}
+
+// the squares a lame leap passes over: along the line for straight and diagonal leaps,
+// and along the longer side first for the others, like a Xiangqi horse.
+fn passes_over(fromc: Loc, delta: Loc) -> Vec<Loc> {
+    let [dx, dy] = delta;
+    let (ux, uy) = (dx.signum(), dy.signum());
+    if dx == 0 || dy == 0 || dx.abs() == dy.abs() {
+        let length = dx.abs().max(dy.abs());
+        (1..length).map(|k| [fromc[0] + ux * k, fromc[1] + uy * k]).collect()
+    } else if dy.abs() > dx.abs() {
+        (1..dy.abs()).map(|k| [fromc[0], fromc[1] + uy * k]).collect()
+    } else {
+        (1..dx.abs()).map(|k| [fromc[0] + ux * k, fromc[1]]).collect()
+    }
+}
+
+// the squares along one of an atom's directions, from 'fromc'.
+fn ride(board: &Board, fromc: Loc, atom: &Atom, delta: Loc, is_white: bool, vector: &mut Vec<MoveData>) {
+    let mut hurdle = false;
+    let mut testc = fromc;
+    let mut n = 0;
+
+    loop {
+        let lastc = testc;
+        testc = [testc[0] + delta[0], testc[1] + delta[1]];
+        n += 1;
+        if atom.range != 0 && n > atom.range && atom.hop == Hop::No {
+            break;
+        }
+
+        if atom.lame && passes_over(lastc, delta).iter().any(|loc| read_board(board, *loc) != Some(Open)) {
+            break;
+        }
+
+        let to = match read_board(board, testc) {
+            Some(to) => to,
+            None => {break;}
+        };
+
+        // hoppers have to get over something before they can go anywhere.
+        if atom.hop != Hop::No && !hurdle {
+            hurdle = to != Open;
+            continue;
+        }
+
+        match to {
+            Open => if atom.moves {
+                vector.push(MoveData { relation: Empty, to: testc });
+            },
+            piece => {
+                if atom.captures && piece.is_white() != is_white {
+                    vector.push(MoveData { relation: Enemy, to: testc });
+                }
+                break;
+            }
+        }
+
+        if atom.range == 1 || atom.hop == Hop::Grasshopper {
+            break;
+        }
+    }
+}
+
+// every move the made up piece at 'fromc' has.
+pub fn move_list(board: &Board, fromc: Loc, from: Space, vector: &mut Vec<MoveData>) {
+
+    let (is_white, index, moved) = match from {
+        Fairy(w, index, moved) => (w, index, moved),
+        _ => {return;}
+    };
+
+    for atom in &piece(index).atoms {
+        if atom.initial && moved {
+            continue;
+        }
+        for step in &atom.steps {
+            ride(board, fromc, atom, orient(*step, is_white), is_white, vector);
+        }
+    }
+}
+
+// how many of 'delta' it takes to get 'diff', if a whole number of them does.
+fn steps_to(diff: Loc, delta: Loc) -> Option<isize> {
+    let k = if delta[0] != 0 {diff[0] / delta[0]} else {diff[1] / delta[1]};
+    (k > 0 && diff == [delta[0] * k, delta[1] * k]).then_some(k)
+}
+
+// whether the made up piece at 'fromc' could take on 'toc'. only the lines that lead there are followed,
+// which is all check detection needs and a lot less than every move the piece has.
+pub fn attacks(board: &Board, fromc: Loc, from: Space, toc: Loc) -> bool {
+
+    let (is_white, index, moved) = match from {
+        Fairy(w, index, moved) => (w, index, moved),
+        _ => {return false;}
+    };
+    let diff = [toc[0] - fromc[0], toc[1] - fromc[1]];
+
+    piece(index).atoms.iter().filter(|atom| atom.captures && !(atom.initial && moved)).any(|atom| {
+        atom.steps.iter().any(|step| {
+            let delta = orient(*step, is_white);
+            let reaches = steps_to(diff, delta).is_some_and(|k| atom.hop != Hop::No || atom.range == 0 || k <= atom.range);
+            if !reaches {
+                return false;
+            }
+            let mut line = Vec::new();
+            ride(board, fromc, atom, delta, is_white, &mut line);
+            line.iter().any(|movement| movement.to == toc && movement.relation == Enemy)
+        })
+    })
+}
@@ -1,18 +1,56 @@
 
 
-use crate::{piece::move_list, check::deep_checks, types::Direction};
+use crate::{piece::{castle_check, castle_rook, castle_targets}, types::{Direction, IllegalMove, Promotion}, variant::Variant};
 
 use super::types::{Space::*, Space, PawnState, Movement, Movement::*};
 
 
 pub type Loc = [isize; 2];
-pub type Board = [[Space; 8];8];
 
+// the most files or ranks a board can have.
+pub const MAX_SIZE: usize = 10;
 
+// the squares are stored top rank first, the way a FEN lists them,
+// and only the top left 'width' by 'height' of them are part of the board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Board {
+    pub squares: [[Space; MAX_SIZE]; MAX_SIZE],
+    pub width: isize,
+    pub height: isize
+}
+
+impl Board {
+    pub const fn empty(width: isize, height: isize) -> Board {
+        Board { squares: [[Open; MAX_SIZE]; MAX_SIZE], width, height }
+    }
+
+    // a board laid out rank by rank, the top rank first.
+    pub const fn from_ranks<const W: usize, const H: usize>(ranks: [[Space; W]; H]) -> Board {
+        let mut board = Board::empty(W as isize, H as isize);
+        let mut y = 0;
+        while y < H {
+            let mut x = 0;
+            while x < W {
+                board.squares[y][x] = ranks[y][x];
+                x += 1;
+            }
+            y += 1;
+        }
+        board
+    }
 
+    pub fn contains(&self, loc: Loc) -> bool {
+        (1..=self.width).contains(&loc[0]) && (1..=self.height).contains(&loc[1])
+    }
 
+    // every space on the board, top rank first.
+    pub fn spaces(&self) -> impl Iterator<Item = Space> + '_ {
+        self.squares.iter().take(self.height as usize).flat_map(|rank| rank.iter().take(self.width as usize).copied())
+    }
+}
 
-pub const STANDARD_BOARD: Board =
+
+pub const STANDARD_BOARD: Board = Board::from_ranks(
     [[Rook(false, false), Knight(false), Bishop(false), Queen(false), King(false, false), Bishop(false), Knight(false), Rook(false, false)],
     [Pawn(false, PawnState::NotMoved); 8],
     [Open; 8],
@@ -21,9 +59,9 @@ pub const STANDARD_BOARD: Board =
     [Open; 8],
     [Pawn(true, PawnState::NotMoved); 8],
     [Rook(true, false), Knight(true), Bishop(true), Queen(true), King(true, false), Bishop(true), Knight(true), Rook(true, false)]
-    ];
+    ]);
 
-pub const NO_PAWNS: Board =
+pub const NO_PAWNS: Board = Board::from_ranks(
     [[Rook(false, false), Knight(false), Bishop(false), Queen(false), King(false, false), Bishop(false), Knight(false), Rook(false, false)],
     [Open; 8],
     [Open; 8],
@@ -32,40 +70,103 @@ pub const NO_PAWNS: Board =
     [Open; 8],
     [Open; 8],
     [Rook(true, false), Knight(true), Bishop(true), Queen(true), King(true, false), Bishop(true), Knight(true), Rook(true, false)]
-    ];
+    ]);
 
 pub fn read_board(board: &Board, r: Loc) -> Option<Space> {
-    if r[0] < 1 || r[0] > 8 || r[1] < 1 || r[1] > 8 {
+    if !board.contains(r) {
         return None;
     }
 
-    Some(board[r[1] as usize - 1][r[0] as usize - 1])
+    Some(board.squares[r[1] as usize - 1][r[0] as usize - 1])
 }
 
 pub fn write_board(board: &mut Board, loc: Loc, space: Space) -> bool {
-    if loc[0] < 1 || loc[0] > 8 || loc[1] < 1 || loc[1] > 8 {
+    if !board.contains(loc) {
         return false;
     }
 
-    board[loc[1] as usize - 1][loc[0] as usize - 1] = space;
+    board.squares[loc[1] as usize - 1][loc[0] as usize - 1] = space;
     true
 }
 
-pub fn move_piece(board: &mut Board, fromc: Loc, from: Space, toc: Loc, to: Space) -> bool {
+// tries to move the piece at 'fromc' to 'toc' for the team whose turn it is under the rules of 'variant',
+// explaining why if the move isn't allowed. a pawn reaching the far end becomes 'promotion'.
+pub fn move_piece(variant: &dyn Variant, board: &mut Board, fromc: Loc, toc: Loc, turn_white: bool, promotion: Promotion) -> Result<(), IllegalMove> {
 
-    let mut moveset = move_list(board, fromc, from);
-    deep_checks(board, fromc, &mut moveset);
+    let from = match read_board(board, fromc) {
+        Some(Open) | None => {return Err(IllegalMove::NoPiece);},
+        Some(piece) => piece
+    };
 
-    for valid_move in moveset {
-        if valid_move.to == toc {
-            return do_move(board, fromc, from, toc, valid_move.relation);
+    if from.is_white() != turn_white {
+        return Err(IllegalMove::WrongSide);
+    }
+
+    for valid_move in variant.moves(board, fromc) {
+
+        // castling can also be asked for by moving the king onto its own rook,
+        // which is the only way to say it in Chess960 when the king doesn't move far.
+        let rook_square = match valid_move.relation {
+            KingSide => castle_rook(board, fromc, turn_white, Direction::East),
+            QueenSide => castle_rook(board, fromc, turn_white, Direction::West),
+            _ => None
+        };
+
+        if valid_move.to == toc || rook_square == Some(toc) {
+            return match valid_move.relation {
+                Check => Err(variant.check_reason(board, fromc, toc)),
+                Blocked => Err(IllegalMove::NotReachable),
+                relation => {
+                    let promotions = variant.promotions_at(board, fromc, toc);
+                    if !promotions.is_empty() && !promotions.contains(&promotion) {
+                        return Err(IllegalMove::CantPromoteTo);
+                    }
+                    do_move(board, fromc, from, toc, relation);
+                    variant.after_move(board, fromc, toc, relation, promotion);
+                    Ok(())
+                }
+            };
         }
     };
-    return false;
+
+    // a king heading for its castling square, or onto one of its own rooks, is trying to castle.
+    if let (King(_, _), true) = (from, variant.castles()) {
+        for dir in [Direction::East, Direction::West] {
+            let (king_to, _) = castle_targets(board, fromc, dir);
+            let onto_rook = match read_board(board, toc) {
+                Some(Rook(w, _)) => w == turn_white && (toc[0] > fromc[0]) == (dir == Direction::East),
+                _ => false
+            };
+
+            if toc[1] == fromc[1] && (toc == king_to || onto_rook) {
+                castle_check(board, fromc, from, dir)?;
+            }
+        }
+    }
+
+    Err(IllegalMove::NotReachable)
+}
+
+// a pawn can only be taken en pessant on the move right after it skipped,
+// so any skips left by the opponent run out once this team moves.
+fn expire_skips(board: &mut Board, is_white: bool) {
+    for row in board.squares.iter_mut() {
+        for space in row.iter_mut() {
+            if let Pawn(w, PawnState::PrevSkipped) = *space {
+                if w != is_white {
+                    *space = Pawn(w, PawnState::Moved);
+                }
+            }
+        }
+    }
 }
 
 pub fn do_move(board: &mut Board, fromc: Loc, from: Space, toc: Loc, relation: Movement) -> bool {
 
+    if from != Open && relation != Blocked && relation != Check {
+        expire_skips(board, from.is_white());
+    }
+
     match relation {
         PawnSkip => {
             write_board(board, fromc, Open);
@@ -79,17 +180,19 @@ pub fn do_move(board: &mut Board, fromc: Loc, from: Space, toc: Loc, relation: M
                 Direction::West
             };
 
+            // the king and rook can start anywhere in Chess960, so 'toc' isn't used;
+            // they always finish on the usual squares.
+            let is_white = from.is_white();
+            let rookc = match castle_rook(board, fromc, is_white, dir) {
+                Some(rookc) => rookc,
+                None => {return false;}
+            };
+            let (king_to, rook_to) = castle_targets(board, fromc, dir);
+
             write_board(board, fromc, Open);
-            write_board(board, toc, King(from.is_white(), true));
-
-            write_board(board, dir.translate(fromc, 1), Rook(from.is_white(), true));
-            write_board(board, dir.translate(fromc,
-                if dir == Direction::East {
-                    3
-                } else {
-                    4
-                }
-            ), Open)
+            write_board(board, rookc, Open);
+            write_board(board, rook_to, Rook(is_white, true));
+            write_board(board, king_to, King(is_white, true))
         },
 
         Enemy | Empty => {
@@ -101,6 +204,9 @@ pub fn do_move(board: &mut Board, fromc: Loc, from: Space, toc: Loc, relation: M
                 Rook(w, _) => {
                     write_board(board, toc, Rook(w, true));
                 },
+                Fairy(w, index, _) => {
+                    write_board(board, toc, Fairy(w, index, true));
+                },
                 Pawn(w, state) => {
 
                     write_board(board, toc, Pawn(w,
@@ -131,6 +237,9 @@ pub fn do_move(board: &mut Board, fromc: Loc, from: Space, toc: Loc, relation: M
             write_board(board, toc, from);
             write_board(board, dir.translate(toc, 1), Open)
         },
+        Drop => {
+            write_board(board, toc, from)
+        },
         Blocked | Check => {
             false
         }
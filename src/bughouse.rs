@@ -0,0 +1,32 @@
+use crate::game::{Game, Outcome, side};
+
+
+// ties the two boards of a Bughouse game together. partners play opposite colours,
+// so white on one board and black on the other are a team.
+
+// hands whatever was taken on each board to the partner on the other,
+// and ends both boards once either of them is over.
+pub fn settle(first: &mut Game, second: &mut Game) {
+    hand_over(first, second);
+    hand_over(second, first);
+
+    match (first.outcome, second.outcome) {
+        (Some(outcome), None) => second.outcome = Some(on_other_board(outcome)),
+        (None, Some(outcome)) => first.outcome = Some(on_other_board(outcome)),
+        _ => ()
+    }
+}
+
+fn hand_over(from: &mut Game, to: &mut Game) {
+    for (is_white, kind) in from.passing.drain(..) {
+        to.pockets[side(!is_white)][kind] += 1;
+    }
+}
+
+// the same result as seen from the other board, where the winners' partner plays the other colour.
+fn on_other_board(outcome: Outcome) -> Outcome {
+    match outcome.winner() {
+        Some(is_white) => Outcome::OtherBoard(!is_white),
+        None => outcome
+    }
+}
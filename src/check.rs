@@ -1,32 +1,37 @@
 
-use crate::board::{Board, Loc, read_board, do_move};
-use crate::piece::{KNIGHT_MOVES, move_list};
-use crate::types::{MoveData, Movement::*, Space, Space::*, Direction};
+use crate::board::{Board, Loc, read_board, write_board, do_move};
+use crate::betza;
+use crate::piece::KNIGHT_MOVES;
+use crate::variant::Variant;
+use crate::types::{IllegalMove, MoveData, Movement::*, Space, Space::*, Direction};
 
-pub fn deep_checks(board: &Board, fromc: Loc, vector: &mut Vec<MoveData>) {
+pub fn deep_checks(board: &Board, fromc: Loc, vector: &mut [MoveData]) {
 
-    for index in 0..vector.len() {
-        //println!("iteration {} of {}", index, vector.len());
+    for movement in vector.iter_mut() {
 
-        let (toc, relation) = {
-            let movement = vector[index];
-            (movement.to, movement.relation)
-        };
+        let (toc, relation) = (movement.to, movement.relation);
 
-        let mut test_board = board.clone();
+        let mut test_board = *board;
         let from = read_board(&test_board, fromc).unwrap();
 
         do_move(&mut test_board, fromc, from, toc, relation);
-        if is_check(&test_board, get_king(board, from.is_white()).unwrap(), from.is_white()) {
-            vector[index] = MoveData {relation: Check, to: toc};
+        // a side without a king, like the Horde, can't be put in check.
+        let kingc = match (from, get_king(board, from.is_white())) {
+            (King(_, _), _) => toc,
+            (_, Some(kingc)) => kingc,
+            (_, None) => {continue;}
+        };
+
+        if is_check(&test_board, kingc, from.is_white()) {
+            *movement = MoveData {relation: Check, to: toc};
         }
     }
 }
 
-fn get_king(board: &Board, is_white: bool) -> Option<Loc> {
+pub fn get_king(board: &Board, is_white: bool) -> Option<Loc> {
 
-    for x in 1..9 {
-        for y in 1..9 {
+    for x in 1..=board.width {
+        for y in 1..=board.height {
             let test = read_board(board, [x, y]);
             if let Some(King(w, _)) = test {
                 if w == is_white {
@@ -39,22 +44,39 @@ fn get_king(board: &Board, is_white: bool) -> Option<Loc> {
 }
 
 pub fn is_check(board: &Board, kingc: Loc, is_white: bool) -> bool {
+    scan(board, kingc, is_white, &mut |_| true)
+}
+
+// every piece of the other team attacking a square of the given team's, pins and all.
+pub fn attackers(board: &Board, targetc: Loc, is_white: bool) -> Vec<Loc> {
+    let mut found = Vec::new();
+    scan(board, targetc, is_white, &mut |fromc| {
+        found.push(fromc);
+        false
+    });
+    found
+}
+
+// hands each piece of the other team that attacks 'kingc' to 'found', until it says that's enough.
+// gives back whether it did.
+fn scan(board: &Board, kingc: Loc, is_white: bool, found: &mut impl FnMut(Loc) -> bool) -> bool {
 
     // checks for pawns first :)
-    'pawn: {
+    {
+        // enemy pawns attack from the side they are moving away from.
         let pawn_dir = match is_white {
             true => {
-                Direction::North
+                Direction::South
             },
             false => {
-                Direction::South
+                Direction::North
             }
         }.translate(kingc, 1);
 
         let testc = Direction::West.translate(pawn_dir, 1);
         let test = read_board(board, testc);
         if let Some(Pawn(w, _)) = test {
-            if w != is_white {
+            if w != is_white && found(testc) {
                 return true;
             }
         }
@@ -62,31 +84,27 @@ pub fn is_check(board: &Board, kingc: Loc, is_white: bool) -> bool {
         let testc = Direction::East.translate(pawn_dir, 1);
         let test = read_board(board, testc);
         if let Some(Pawn(w, _)) = test {
-            if w != is_white {
+            if w != is_white && found(testc) {
                 return true;
             }
         }
     }
 
-    'rook: {
+    // rooks
+    {
         for dir in Direction::CARDINALS {
-            for index in 1..9 {
+            for index in 1.. {
                 let testc = dir.translate(kingc, index);
                 let test = read_board(board, testc);
 
                 match test {
-                    Some(Rook(w, _)) | Some(Queen(w)) => {
+                    Some(Rook(w, _)) | Some(Queen(w)) | Some(Chancellor(w)) => {
 
-                        if w != is_white {
+                        if w != is_white && found(testc) {
                             return true;
                         }
                         break;
                     },
-                    Some(King(w, _)) => if index == 1 {
-                        if w != is_white {
-                            return true;
-                        }
-                    }
                     Some(Open) => {},
                     Some(_) | None => {
                         break;
@@ -96,29 +114,42 @@ pub fn is_check(board: &Board, kingc: Loc, is_white: bool) -> bool {
         }
     }
 
-    'knight: {
+    // kings can't walk next to each other.
+    {
+        for dir in Direction::CARDINALS.into_iter().chain(Direction::ORDINALS) {
+            let testc = dir.translate(kingc, 1);
+            if let Some(King(w, _)) = read_board(board, testc) {
+                if w != is_white && found(testc) {
+                    return true;
+                }
+            }
+        }
+    }
+
+    // knights
+    {
         for delta in KNIGHT_MOVES {
 
             let testc = [kingc[0] + delta[0], kingc[1] + delta[1]];
             let test = read_board(board, testc);
-            match test {
-                Some(Knight(w)) => if w != is_white {
+            if let Some(Knight(w)) | Some(Archbishop(w)) | Some(Chancellor(w)) = test {
+                if w != is_white && found(testc) {
                     return true;
-                },
-                _ => {}
+                }
             }
         }
     }
 
-    'bishop: {
+    // bishops
+    {
         for dir in Direction::ORDINALS {
-            for index in 1..9 {
+            for index in 1.. {
                 let testc = dir.translate(kingc, index);
                 let test = read_board(board, testc);
 
                 match test {
-                    Some(Bishop(w)) | Some(Queen(w)) => {
-                        if w != is_white {
+                    Some(Bishop(w)) | Some(Queen(w)) | Some(Archbishop(w)) => {
+                        if w != is_white && found(testc) {
                             return true;
                         }
                         break;
@@ -131,55 +162,83 @@ pub fn is_check(board: &Board, kingc: Loc, is_white: bool) -> bool {
             }
         }
     }
+
+    // made up pieces move any which way, so the only way to know is to ask them,
+    // though only about the lines that lead to the king.
+    if betza::any() {
+        for y in 1..=board.height {
+            for x in 1..=board.width {
+                if let Some(piece @ Fairy(w, _, _)) = read_board(board, [x, y]) {
+                    if w != is_white && betza::attacks(board, [x, y], piece, kingc) && found([x, y]) {
+                        return true;
+                    }
+                }
+            }
+        }
+    }
     false
 }
 
-// the 'is_white' variable is the team that is defending;
-// the one getting "checkmated" if you will.
-pub fn is_checkmated(board: &Board, is_white: bool) -> bool {
+// true if the king of the given team is currently attacked.
+pub fn in_check(board: &Board, is_white: bool) -> bool {
+    match get_king(board, is_white) {
+        Some(kingc) => is_check(board, kingc, is_white),
+        None => false
+    }
+}
+
+// works out why a move that deep_checks marked as 'Check' isn't allowed.
+pub fn check_reason(board: &Board, fromc: Loc, from: Space) -> IllegalMove {
 
+    let is_white = from.is_white();
 
-    let kingc = get_king(board, is_white).unwrap();
+    if let King(_, _) = from {
+        return IllegalMove::KingInCheck;
+    }
 
-    // compiles all opposing pieces.
+    if in_check(board, is_white) {
+        return IllegalMove::KingInCheck;
+    }
 
-    if !is_check(board, kingc, is_white) {
-        return false;
+    // if lifting the piece off the board exposes the king, it was pinned.
+    let mut test_board = *board;
+    write_board(&mut test_board, fromc, Open);
+    if in_check(&test_board, is_white) {
+        IllegalMove::Pinned
+    } else {
+        IllegalMove::KingInCheck
     }
+}
 
-    let mut vector = Vec::new();
+// true if the team has any move that doesn't leave its own king in check.
+pub fn has_legal_move<V: Variant + ?Sized>(variant: &V, board: &Board, is_white: bool) -> bool {
 
-    for y in 1..9 {
-        for x in 1..9 {
-            let testc = [x, y];
-            let test = read_board(board, testc);
+    for y in 1..=board.height {
+        for x in 1..=board.width {
+            let fromc = [x, y];
 
-            match test {
-                Some(Open) | None => {
-                    continue;
-                },
+            match read_board(board, fromc) {
+                Some(Open) | None => {},
                 Some(piece) => if piece.is_white() == is_white {
-                    vector.push((testc, piece));
+
+                    let vector = variant.moves(board, fromc);
+                    if vector.iter().any(|movement| movement.relation != Check && movement.relation != Blocked) {
+                        return true;
+                    }
                 }
             }
         }
     }
+    false
+}
 
-    for (fromc, from) in vector {
-        for movement in move_list(board, fromc, from) {
-
-            let mut test_board = board.clone();
-            do_move(&mut test_board, fromc, from, movement.to, movement.relation);
-            let kingc = if let King(_, _) = from {
-                movement.to
-            } else {
-                kingc
-            };
+// no legal moves, but not in check either.
+pub fn is_stalemated<V: Variant + ?Sized>(variant: &V, board: &Board, is_white: bool) -> bool {
+    !variant.in_check(board, is_white) && !has_legal_move(variant, board, is_white)
+}
 
-            if !is_check(&test_board, kingc, is_white) {
-                return false;
-            }
-        }
-    }
-    true
+// the 'is_white' variable is the team that is defending;
+// the one getting "checkmated" if you will.
+pub fn is_checkmated<V: Variant + ?Sized>(variant: &V, board: &Board, is_white: bool) -> bool {
+    variant.in_check(board, is_white) && !has_legal_move(variant, board, is_white)
 }
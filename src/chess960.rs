@@ -0,0 +1,60 @@
+use rand::Rng;
+use crate::{board::Board, types::{PawnState, Space, Space::*}};
+
+
+// Chess960 (Fischer Random) starting positions, numbered the way Scharnagl did,
+// so that 518 is the standard setup.
+pub const POSITIONS: u16 = 960;
+
+// where the two knights go among the five squares left once the bishops and queen are placed.
+const KNIGHTS: [(usize, usize); 10] = [(0, 1), (0, 2), (0, 3), (0, 4), (1, 2), (1, 3), (1, 4), (2, 3), (2, 4), (3, 4)];
+
+pub fn random() -> u16 {
+    rand::thread_rng().gen_range(0..POSITIONS)
+}
+
+// the pieces on the first rank from the a file to the h file, for either side.
+pub fn back_rank(number: u16, is_white: bool) -> [Space; 8] {
+
+    let mut rank = [Open; 8];
+    let mut n = (number % POSITIONS) as usize;
+
+    // bishops go on opposite colours.
+    rank[n % 4 * 2 + 1] = Bishop(is_white);
+    n /= 4;
+    rank[n % 4 * 2] = Bishop(is_white);
+    n /= 4;
+
+    let place = |rank: &mut [Space; 8], nth: usize, piece: Space| {
+        let x = (0..8).filter(|x| rank[*x] == Open).nth(nth).unwrap();
+        rank[x] = piece;
+    };
+
+    place(&mut rank, n % 6, Queen(is_white));
+    n /= 6;
+
+    // the second knight is counted before the first is placed.
+    let (first, second) = KNIGHTS[n];
+    place(&mut rank, second, Knight(is_white));
+    place(&mut rank, first, Knight(is_white));
+
+    // the king always ends up between the rooks.
+    place(&mut rank, 0, Rook(is_white, false));
+    place(&mut rank, 0, King(is_white, false));
+    place(&mut rank, 0, Rook(is_white, false));
+
+    rank
+}
+
+pub fn board(number: u16) -> Board {
+    Board::from_ranks([
+        back_rank(number, false),
+        [Pawn(false, PawnState::NotMoved); 8],
+        [Open; 8],
+        [Open; 8],
+        [Open; 8],
+        [Open; 8],
+        [Pawn(true, PawnState::NotMoved); 8],
+        back_rank(number, true)
+    ])
+}
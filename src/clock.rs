@@ -0,0 +1,101 @@
+use std::{fmt, time::{Duration, Instant}};
+
+
+// a starting amount of time, plus a bonus for every move made.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimeControl {
+    pub base: Duration,
+    pub increment: Duration
+}
+
+impl TimeControl {
+
+    pub const fn minutes(base: u64, increment: u64) -> TimeControl {
+        TimeControl {
+            base: Duration::from_secs(base * 60),
+            increment: Duration::from_secs(increment)
+        }
+    }
+
+    // the choices offered when setting up a game.
+    pub const PRESETS: [TimeControl; 6] = [
+        TimeControl::minutes(1, 0),
+        TimeControl::minutes(3, 2),
+        TimeControl::minutes(5, 0),
+        TimeControl::minutes(10, 5),
+        TimeControl::minutes(15, 10),
+        TimeControl::minutes(30, 0)
+    ];
+}
+
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}+{}", self.base.as_secs() / 60, self.increment.as_secs())
+    }
+}
+
+
+// a chess clock; only the side to move has their time running.
+#[derive(Clone, Copy, Debug)]
+pub struct Clock {
+    // time left for white and black, as of 'started'.
+    remaining: [Duration; 2],
+    increment: Duration,
+    started: Instant,
+    white_running: bool
+}
+
+fn side(is_white: bool) -> usize {
+    match is_white {
+        true => 0,
+        false => 1
+    }
+}
+
+impl Clock {
+
+    // white's time starts running straight away.
+    pub fn new(control: TimeControl) -> Clock {
+        Clock {
+            remaining: [control.base; 2],
+            increment: control.increment,
+            started: Instant::now(),
+            white_running: true
+        }
+    }
+
+    pub fn time_left(&self, is_white: bool) -> Duration {
+        let left = self.remaining[side(is_white)];
+        if is_white == self.white_running {
+            left.saturating_sub(self.started.elapsed())
+        } else {
+            left
+        }
+    }
+
+    pub fn increment(&self) -> Duration {
+        self.increment
+    }
+
+    pub fn flagged(&self, is_white: bool) -> bool {
+        self.time_left(is_white).is_zero()
+    }
+
+    // called once a side has finished their move; hands the clock over to the other side.
+    pub fn press(&mut self) {
+        let mover = self.white_running;
+        self.remaining[side(mover)] = self.time_left(mover) + self.increment;
+        self.white_running = !mover;
+        self.started = Instant::now();
+    }
+
+    // "m:ss", or "s.t" once there is less than ten seconds left.
+    pub fn format(&self, is_white: bool) -> String {
+        let left = self.time_left(is_white);
+        if left < Duration::from_secs(10) {
+            format!("{}.{}", left.as_secs(), left.subsec_millis() / 100)
+        } else {
+            format!("{}:{:02}", left.as_secs() / 60, left.as_secs() % 60)
+        }
+    }
+}
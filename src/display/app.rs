@@ -0,0 +1,387 @@
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::UnboundedSender;
+use crate::{board::{Board, STANDARD_BOARD, NO_PAWNS}, bughouse::settle, chess960, clock::TimeControl, fen::to_fen, game::{Game, side}, variant::VARIANTS};
+use super::events::{UserState, analysis, computer};
+
+
+// preferences that carry over from one game to the next.
+#[derive(Clone, Copy, Debug)]
+pub struct Settings {
+    pub cursor_blink: bool,
+    pub show_moves: bool,
+    pub warn_hanging: bool
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings { cursor_blink: true, show_moves: true, warn_hanging: true }
+    }
+}
+
+impl Settings {
+
+    pub const COUNT: usize = 3;
+
+    pub fn rows(&self) -> [(&'static str, bool); Settings::COUNT] {
+        [
+            ("Cursor blink", self.cursor_blink),
+            ("Show legal moves", self.show_moves),
+            ("Warn about hanging pieces", self.warn_hanging)
+        ]
+    }
+
+    pub fn toggle(&mut self, row: usize) {
+        match row {
+            0 => self.cursor_blink = !self.cursor_blink,
+            1 => self.show_moves = !self.show_moves,
+            2 => self.warn_hanging = !self.warn_hanging,
+            _ => ()
+        }
+    }
+}
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Opponent {
+    Human,
+    Computer,
+    // the one --engine gave.
+    Engine
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Colour {
+    White,
+    Black,
+    Random
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StartPosition {
+    Standard,
+    NoPawns,
+    Chess960
+}
+
+impl Opponent {
+    pub const ALL: [Opponent; 3] = [Opponent::Human, Opponent::Computer, Opponent::Engine];
+
+    pub fn name(&self) -> String {
+        match self {
+            Opponent::Human => "Human (hot seat)".to_string(),
+            Opponent::Computer => "Computer".to_string(),
+            Opponent::Engine => computer::engine_name().unwrap_or_else(|| "Engine".to_string())
+        }
+    }
+
+    pub fn is_computer(&self) -> bool {
+        *self != Opponent::Human
+    }
+
+    pub fn is_engine(&self) -> bool {
+        *self == Opponent::Engine
+    }
+
+    // there's only an engine to play when one was given.
+    fn available(&self) -> bool {
+        !self.is_engine() || computer::engine_name().is_some()
+    }
+
+    // the next one along that can be played.
+    fn step(index: usize, forward: bool) -> usize {
+        let mut index = step(index, Opponent::ALL.len(), forward);
+        while !Opponent::ALL[index].available() {
+            index = step(index, Opponent::ALL.len(), forward);
+        }
+        index
+    }
+}
+
+impl Colour {
+    pub const ALL: [Colour; 3] = [Colour::White, Colour::Black, Colour::Random];
+
+    pub fn name(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    pub fn is_white(&self) -> bool {
+        match self {
+            Colour::White => true,
+            Colour::Black => false,
+            Colour::Random => rand::random()
+        }
+    }
+}
+
+impl StartPosition {
+    pub const ALL: [StartPosition; 3] = [StartPosition::Standard, StartPosition::NoPawns, StartPosition::Chess960];
+
+    pub fn name(&self) -> String {
+        match self {
+            StartPosition::Standard => "Standard".to_string(),
+            StartPosition::NoPawns => "No pawns".to_string(),
+            StartPosition::Chess960 => "Chess960".to_string()
+        }
+    }
+
+    // 'number' picks the Chess960 setup, or a random one if it's None.
+    pub fn board(&self, number: Option<u16>) -> Board {
+        match self {
+            StartPosition::Standard => STANDARD_BOARD,
+            StartPosition::NoPawns => NO_PAWNS,
+            StartPosition::Chess960 => chess960::board(number.unwrap_or_else(chess960::random))
+        }
+    }
+}
+
+
+// wraps around when stepping past either end of a list.
+pub fn step(index: usize, len: usize, forward: bool) -> usize {
+    match forward {
+        true => (index + 1) % len,
+        false => (index + len - 1) % len
+    }
+}
+
+// the choices made before a game starts; each field is an index into its option list.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NewGameDialog {
+    pub field: usize,
+    pub opponent: usize,
+    pub colour: usize,
+    pub time_control: usize,
+    pub variant: usize,
+    pub start: usize,
+    // the Scharnagl number of the Chess960 setup, None for a random one.
+    pub chess960: Option<u16>,
+    // who plays alongside you on the other board in Bughouse.
+    pub partner: usize
+}
+
+impl NewGameDialog {
+
+    // the seven option rows, then the start button.
+    pub const FIELDS: usize = 8;
+
+    // the row the Chess960 number is typed into.
+    pub const CHESS960_FIELD: usize = 5;
+
+    // index 0 is no clock at all.
+    fn time_control(&self) -> Option<TimeControl> {
+        match self.time_control {
+            0 => None,
+            n => Some(TimeControl::PRESETS[n - 1])
+        }
+    }
+
+    pub fn rows(&self) -> [(&'static str, String); 7] {
+        [
+            ("Opponent", Opponent::ALL[self.opponent].name()),
+            ("Colour", Colour::ALL[self.colour].name()),
+            ("Time", match self.time_control() {
+                Some(control) => control.to_string(),
+                None => "Unlimited".to_string()
+            }),
+            ("Variant", VARIANTS[self.variant].name().to_string()),
+            ("Position", match VARIANTS[self.variant].start() {
+                Some(_) => "Variant's own".to_string(),
+                None => StartPosition::ALL[self.start].name()
+            }),
+            ("960 no.", match self.chess960 {
+                Some(number) => number.to_string(),
+                None => "Random".to_string()
+            }),
+            ("Partner", match VARIANTS[self.variant].passes_captures() {
+                true => Opponent::ALL[self.partner].name(),
+                false => "-".to_string()
+            })
+        ]
+    }
+
+    pub fn cycle(&mut self, forward: bool) {
+        match self.field {
+            0 => self.opponent = Opponent::step(self.opponent, forward),
+            1 => self.colour = step(self.colour, Colour::ALL.len(), forward),
+            2 => self.time_control = step(self.time_control, TimeControl::PRESETS.len() + 1, forward),
+            3 => self.variant = step(self.variant, VARIANTS.len(), forward),
+            4 => self.start = step(self.start, StartPosition::ALL.len(), forward),
+            // 'Random' sits before 0.
+            5 => self.chess960 = match step(self.chess960.map_or(0, |n| n as usize + 1), chess960::POSITIONS as usize + 1, forward) {
+                0 => None,
+                n => Some(n as u16 - 1)
+            },
+            6 => self.partner = Opponent::step(self.partner, forward),
+            _ => ()
+        }
+    }
+
+    // typing a number in starts over once it would go past the last position.
+    pub fn type_digit(&mut self, digit: u16) {
+        let number = self.chess960.unwrap_or(0) * 10 + digit;
+        self.chess960 = Some(if number < chess960::POSITIONS {number} else {digit});
+    }
+
+    pub fn erase_digit(&mut self) {
+        self.chess960 = match self.chess960 {
+            Some(number) if number >= 10 => Some(number / 10),
+            _ => None
+        };
+    }
+
+    pub fn start(&self, settings: Settings) -> UserState {
+        let variant = VARIANTS[self.variant];
+        let board = variant.start().unwrap_or_else(|| StartPosition::ALL[self.start].board(self.chess960));
+        let is_white = Colour::ALL[self.colour].is_white();
+        let opponent = Opponent::ALL[self.opponent];
+        let teammate = Opponent::ALL[self.partner];
+
+        let mut user = UserState::new(Game::new(variant, board, self.time_control()), !is_white, settings);
+        user.computer[side(!is_white)] = opponent.is_computer();
+        user.engine[side(!is_white)] = opponent.is_engine();
+
+        // the partner sits on the other board with the other colour, facing the other opponent.
+        if variant.passes_captures() {
+            let mut partner = UserState::new(Game::new(variant, board, self.time_control()), is_white, settings);
+            partner.board_number = 1;
+            partner.cursor_blink = false;
+            partner.computer[side(!is_white)] = teammate.is_computer();
+            partner.engine[side(!is_white)] = teammate.is_engine();
+            partner.computer[side(is_white)] = opponent.is_computer();
+            partner.engine[side(is_white)] = opponent.is_engine();
+            user.partner = Some(Box::new(partner));
+        }
+        user
+    }
+}
+
+
+pub enum Screen {
+    MainMenu(usize),
+    NewGame(NewGameDialog),
+    InGame(UserState),
+    GameOver(UserState),
+    Settings(usize)
+}
+
+// work that finished in the background (engines thinking, network peers),
+// applied to the app back on the ui thread.
+pub type Task = Box<dyn FnOnce(&mut App) + Send>;
+
+pub struct App {
+    pub screen: Screen,
+    pub settings: Settings,
+    pub running: bool,
+    tasks: UnboundedSender<Task>
+}
+
+impl App {
+
+    pub const MAIN_MENU: [&'static str; 3] = ["New game", "Settings", "Quit"];
+
+    // how often timers are checked while nothing else is happening.
+    pub const TICK: Duration = Duration::from_millis(100);
+
+    pub fn new(tasks: UnboundedSender<Task>) -> App {
+        App {
+            screen: Screen::MainMenu(0),
+            settings: Settings::default(),
+            running: true,
+            tasks
+        }
+    }
+
+    // the variant and position being played or looked at, as a FEN.
+    pub fn position(&self) -> Option<(&'static str, String)> {
+        match &self.screen {
+            Screen::InGame(user) | Screen::GameOver(user) => Some((user.game.variant.name(), to_fen(&user.game))),
+            _ => None
+        }
+    }
+
+    // runs 'work' on its own thread, then hands the result to 'apply' on the ui thread.
+    pub fn spawn<T: Send + 'static>(&self, work: impl FnOnce() -> T + Send + 'static, apply: impl FnOnce(&mut App, T) + Send + 'static) {
+        let tasks = self.tasks.clone();
+        std::thread::spawn(move || {
+            let result = work();
+            // the app may already have quit, in which case there is nobody to tell.
+            let _ = tasks.send(Box::new(move |app: &mut App| apply(app, result)));
+        });
+    }
+
+    // hands each thing 'items' comes up with to 'apply' on the ui thread, as they turn up.
+    pub fn listen<T: Send + 'static>(&self, items: impl Iterator<Item = T> + Send + 'static, apply: fn(T, &mut App)) {
+        let tasks = self.tasks.clone();
+        std::thread::spawn(move || {
+            for item in items {
+                if tasks.send(Box::new(move |app: &mut App| apply(item, app))).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    // keeps timers going, and moves on to the game over screen once a game has finished.
+    // returns true if anything on screen changed.
+    pub fn update(&mut self) -> bool {
+
+        let mut changed = false;
+        let mut wanted = Vec::new();
+        let mut analysing = None;
+
+        if let Screen::InGame(user) = &mut self.screen {
+
+            user.game.tick();
+            if let Some(partner) = &mut user.partner {
+                partner.game.tick();
+                settle(&mut user.game, &mut partner.game);
+                // the other board's clocks keep running too.
+                changed |= partner.game.clock.is_some();
+            }
+
+            // a running clock changes the screen every tick.
+            if user.game.clock.is_some() {
+                changed = true;
+            }
+
+            // the explosion fades out over a few ticks.
+            if let Some(start) = user.explosion {
+                if start.elapsed() >= UserState::EXPLOSION {
+                    user.explosion = None;
+                }
+                changed = true;
+            }
+
+            // cursor blink manager
+            if !user.settings.cursor_blink {
+                changed |= !user.cursor_blink;
+                user.cursor_blink = true;
+            } else if user.blink_timer.elapsed() >= UserState::BLINK {
+                user.cursor_blink = !user.cursor_blink;
+                user.blink_timer = Instant::now();
+                changed = true;
+            }
+
+            wanted = computer::wanted(user);
+            analysing = analysis::wanted(user);
+
+            if user.game.outcome.is_some() {
+                computer::stop(user);
+                user.selected = None;
+                user.cursor_blink = false;
+                if let Screen::InGame(user) = std::mem::replace(&mut self.screen, Screen::MainMenu(0)) {
+                    self.screen = Screen::GameOver(user);
+                }
+                changed = true;
+            }
+        }
+
+        let generation = computer::generation();
+        for (number, game, limits, engine) in wanted {
+            self.spawn(move || computer::think(generation, game, limits, engine), move |app, play| computer::event(number, play, app));
+        }
+        if let Some((game, stop)) = analysing {
+            self.listen(analysis::analyse(game, stop), analysis::event);
+        }
+        changed
+    }
+}
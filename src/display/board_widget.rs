@@ -3,7 +3,7 @@
 
 
 use super::events::UserState;
-use crate::{types::{Space, Movement::*}, board::{Loc, read_board}, piece::move_list, check::deep_checks};
+use crate::{types::{Direction, Space, Movement::*}, board::{Loc, MAX_SIZE, read_board, write_board}, game::side, piece::castle_rook, pocket::drop_squares};
 use ratatui::{
     layout::Rect,
     buffer::{Buffer, Cell},
@@ -12,8 +12,31 @@ use ratatui::{
 };
 
 impl UserState {
-    pub fn get_rect() -> Rect {
-        Rect { x: 5, y: 5, width: 30, height: 10 }
+    // three cells a square, with a square's worth of legend all the way around.
+    pub fn get_rect(&self) -> Rect {
+        let board = &self.game.board;
+        Rect { x: 5, y: 5, width: (board.width as u16 + 2) * 3, height: board.height as u16 + 2 }
+    }
+
+    // where a board location ends up on screen, once the board has been flipped.
+    fn screen_loc(&self, location: Loc) -> Loc {
+        let board = &self.game.board;
+        match self.flipped {
+            true => [board.width + 1 - location[0], board.height + 1 - location[1]],
+            false => location
+        }
+    }
+
+    // the squares the player at the board can see, or None if they can see all of it.
+    // once the game is over everything is shown.
+    fn seen(&self) -> Option<[[bool; MAX_SIZE]; MAX_SIZE]> {
+        let game = &self.game;
+        let viewer = self.viewer();
+        match (game.variant.hides() && game.outcome.is_none(), self.hidden) {
+            (false, _) => None,
+            (true, true) => Some([[false; MAX_SIZE]; MAX_SIZE]),
+            (true, false) => Some(game.variant.visible(&game.board, viewer))
+        }
     }
 }
 
@@ -23,7 +46,8 @@ enum FColor {
     Black
 }
 
-fn write_cell(cells: &mut Vec<Cell>, is_dark: &bool, color: FColor, c: char) -> bool {
+// 'text' is one character in the middle of the square, or two for the tenth rank's number.
+fn write_cell(cells: &mut Vec<Cell>, is_dark: &bool, color: FColor, text: &str) -> bool {
 
     let black_tile: Style = {
 
@@ -48,8 +72,9 @@ fn write_cell(cells: &mut Vec<Cell>, is_dark: &bool, color: FColor, c: char) ->
 
     let mut arr = [Cell::default(), Cell::default(), Cell::default()];
 
+    let mut chars = text.chars();
     arr[0].set_style(style).set_char(' ');
-    arr[1].set_style(style).set_char(c);
+    arr[1].set_style(style).set_char(chars.next().unwrap_or(' '));
 
     match color {
         FColor::White => {arr[1].set_fg(Color::Rgb(230,230,230));},
@@ -57,7 +82,7 @@ fn write_cell(cells: &mut Vec<Cell>, is_dark: &bool, color: FColor, c: char) ->
         _ => ()
     }
 
-    arr[2].set_style(style).set_char(' ');
+    arr[2].set_style(style).set_char(chars.next().unwrap_or(' '));
 
     for cell in arr {
         cells.push(cell);
@@ -66,22 +91,23 @@ fn write_cell(cells: &mut Vec<Cell>, is_dark: &bool, color: FColor, c: char) ->
 }
 
 // stands for "board position to vector cell"
-fn bpvc(location: Loc) -> usize {
-    return ((((location[1]) * 10) + location[0]) * 3) as usize;
+// each row of cells is the board's width plus the legend on either side.
+fn bpvc(width: isize, location: Loc) -> usize {
+    ((((location[1]) * (width + 2)) + location[0]) * 3) as usize
 }
 
-fn set_background_color(location: Loc, color: Color, cells: &mut Vec<Cell>) {
+fn set_background_color(width: isize, location: Loc, color: Color, cells: &mut [Cell]) {
 
-    let i = bpvc(location);
+    let i = bpvc(width, location);
     cells[i].set_bg(color);
     cells[i + 1].set_bg(color);
     cells[i + 2].set_bg(color);
 }
 
 // each grid space will be rendered 3 cells wide, with a char in the center.
-// the chess board is 8x8 spaces, and will be bordered by a numbered legend (or whatever you call it).
+// the chess board is usually 8x8 spaces, and will be bordered by a numbered legend (or whatever you call it).
 
-// the board will be 24 cells wide, 8 tall
+// an 8x8 board will be 24 cells wide, 8 tall
 // including the border, it is 30 wide, 10 tall.
 
 impl Widget for &UserState {
@@ -89,67 +115,136 @@ impl Widget for &UserState {
 
         let mut is_dark = false;
         let mut cells: Vec<Cell> = Vec::new();
-        let top_bottom_row = "~ABCDEFGH~";
+        let board = &self.game.board;
+        let files: Vec<char> = (0..board.width).map(|x| (b'A' + x as u8) as char).collect();
+        let top_bottom_row: String = match self.flipped {
+            true => std::iter::once('~').chain(files.into_iter().rev()).chain(std::iter::once('~')).collect(),
+            false => std::iter::once('~').chain(files).chain(std::iter::once('~')).collect()
+        };
+        let width = board.width;
+        let seen = self.seen();
+        let is_seen = |loc: Loc| seen.is_none_or(|seen| seen[loc[1] as usize - 1][loc[0] as usize - 1]);
+
+        // legal moves are worked out from what can be seen, so they don't give hidden pieces away.
+        let mut shown = *board;
+        for y in 1..=board.height {
+            for x in 1..=board.width {
+                if !is_seen([x, y]) {
+                    write_board(&mut shown, [x, y], Space::Open);
+                }
+            }
+        }
 
         // top row numbers
         for c in top_bottom_row.chars() {
-            is_dark = write_cell(&mut cells, &is_dark, FColor::Auto, c);
+            is_dark = write_cell(&mut cells, &is_dark, FColor::Auto, &c.to_string());
         }
 
         // render board and side numbers.
-        let mut row_n:usize = 8;
-        for row in self.board.iter() {
+        for screen_y in 1..=board.height {
 
-            row_n = row_n - 1;
-            let n = ['1', '2', '3', '4', '5', '6', '7', '8'][row_n];
+            let y = self.screen_loc([1, screen_y])[1];
+            let n = (board.height + 1 - y).to_string();
 
-            is_dark = write_cell(&mut cells, &!is_dark, FColor::Auto, n);
-            for piece in row {
+            // with an even number of files the colours shift along a row, and with an odd number they don't.
+            is_dark = write_cell(&mut cells, &(is_dark != (width % 2 == 0)), FColor::Auto, &n);
+            for screen_x in 1..=board.width {
 
+                let loc = self.screen_loc([screen_x, screen_y]);
+                let piece = match is_seen(loc) {
+                    true => read_board(board, loc).unwrap(),
+                    false => Space::Open
+                };
                 is_dark = write_cell(&mut cells, &is_dark,
-                    if Space::Open == *piece {
+                    if Space::Open == piece {
                         FColor::Auto
+                    } else if piece.is_white() {
+                        FColor::White
                     } else {
-                        if piece.is_white() {
-                            FColor::White
-                        } else {
-                            FColor::Black
-                        }
+                        FColor::Black
                     },
-                    piece.clone().into()
+                    &char::from(piece).to_string()
                 );
             }
-            is_dark = write_cell(&mut cells, &is_dark, FColor::Auto, n);
+            is_dark = write_cell(&mut cells, &is_dark, FColor::Auto, &n);
         }
 
         // bottom row numbers
-        is_dark = !is_dark;
+        is_dark = is_dark != (width % 2 == 0);
         for c in top_bottom_row.chars() {
-            is_dark = write_cell(&mut cells, &is_dark, FColor::Auto, c);
+            is_dark = write_cell(&mut cells, &is_dark, FColor::Auto, &c.to_string());
         }
+        // squares out of sight are dimmed.
+        for y in 1..=board.height {
+            for x in 1..=board.width {
+                if !is_seen([x, y]) {
+                    set_background_color(width, self.screen_loc([x, y]), Color::Rgb(24, 24, 24), &mut cells);
+                }
+            }
+        }
+
         // renders cursors.
 
         if let Some(c) = self.selected {
-            set_background_color(c, Color::Rgb(220,139,0), &mut cells);
-            let from = read_board(&self.board, c).unwrap();
+            set_background_color(width, self.screen_loc(c), Color::Rgb(220,139,0), &mut cells);
+            let from = read_board(board, c).unwrap();
+
+            if self.settings.show_moves {
+                for move_data in self.game.variant.moves(&shown, c) {
+
+                    // castling can also be done by picking the rook, which is the only way when the king doesn't move.
+                    let castle_dir = match move_data.relation {
+                        KingSide => Some(Direction::East),
+                        QueenSide => Some(Direction::West),
+                        _ => None
+                    };
+                    if let Some(dir) = castle_dir {
+                        if let Some(rookc) = castle_rook(board, c, from.is_white(), dir) {
+                            set_background_color(width, self.screen_loc(rookc), Color::Rgb(13, 255, 00), &mut cells);
+                        }
+                    }
+                    if move_data.to == c {
+                        continue;
+                    }
 
-            let mut move_list = move_list(&self.board, c, from);
-            deep_checks(&self.board, c, &mut move_list);
-            for move_data in move_list {
+                    set_background_color(width, self.screen_loc(move_data.to), match move_data.relation {
 
-                set_background_color(move_data.to, match move_data.relation {
+                        Empty | PawnSkip | QueenSide | KingSide | EnPessant | Drop => Color::Rgb(13, 255, 00),
+                        Enemy => Color::Rgb(255, 70, 70),
+                        Blocked => continue,
+                        Check => Color::Rgb(32, 48, 32)
 
-                    Empty | PawnSkip | QueenSide | KingSide | EnPessant => Color::Rgb(13, 255, 00),
-                    Enemy => Color::Rgb(255, 70, 70),
-                    Blocked => continue,
-                    Check => Color::Rgb(32, 48, 32)
+                    }, &mut cells);
+                }
+            }
+        }
+
+        if let (Some(kind), true) = (self.dropping, self.settings.show_moves) {
+            let pocket = &self.game.pockets[side(self.game.turn_white)];
+            for toc in drop_squares(board, pocket, kind, self.game.turn_white) {
+                set_background_color(width, self.screen_loc(toc), Color::Rgb(13, 255, 00), &mut cells);
+            }
+        }
 
-                }, &mut cells);
+        // the blast from the last capture, going from yellow through to a dull red.
+        if let (Some(start), Some(centre)) = (self.explosion, self.game.last_capture) {
+            let color = match start.elapsed().as_millis() * 3 / UserState::EXPLOSION.as_millis() {
+                0 => Color::Rgb(255, 230, 80),
+                1 => Color::Rgb(255, 140, 0),
+                _ => Color::Rgb(160, 30, 0)
+            };
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    let loc = [centre[0] + dx, centre[1] + dy];
+                    if read_board(board, loc).is_some() {
+                        set_background_color(width, self.screen_loc(loc), color, &mut cells);
+                    }
+                }
             }
         }
 
         if self.cursor_blink {
-            set_background_color(self.cursor, Color::Rgb(23,74,255), &mut cells);
+            set_background_color(width, self.screen_loc(self.cursor), Color::Rgb(23,74,255), &mut cells);
         }
 
         // maps the local Vec<Cell> to the full terminal buffer.
@@ -162,7 +257,7 @@ impl Widget for &UserState {
                 cell.set_style(copier.style());
                 cell.set_symbol(copier.symbol.as_str());
 
-                i = i +1;
+                i += 1;
             }
         }
     }
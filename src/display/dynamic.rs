@@ -1,18 +1,36 @@
-use std::{io, thread, time::Duration};
-use ratatui::{
-    backend::CrosstermBackend, Terminal,
-    widgets::{Block, Borders},
-};
+use std::{io, panic, process, sync::Mutex, thread, time::{SystemTime, UNIX_EPOCH}};
+use std::{backtrace::Backtrace, fs, path::PathBuf};
+use ratatui::{backend::CrosstermBackend, Terminal};
 use crossterm::{
     execute,
-    event::{DisableMouseCapture, EnableMouseCapture},
+    cursor::Show,
+    event::DisableMouseCapture,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen}
 };
 
-use super::events;
+use crate::{game::Game, net::Peer, variant};
+use super::{app::{App, Screen}, events, menu_widget};
 
 pub type TerminalC = Terminal<CrosstermBackend<io::Stdout>>;
 
+// the variant and position on the board as of the last redraw, so a crash report can include it.
+static LAST_POSITION: Mutex<Option<(&'static str, String)>> = Mutex::new(None);
+
+pub fn record_position(game: Option<(&'static str, String)>) {
+    if let Ok(mut position) = LAST_POSITION.lock() {
+        *position = game;
+    }
+}
+
+// the name and path of a variant loaded from a file, since --variant can't find it again by name.
+static VARIANT_FILE: Mutex<Option<(&'static str, String)>> = Mutex::new(None);
+
+pub fn record_variant_file(name: &'static str, path: &str) {
+    if let Ok(mut file) = VARIANT_FILE.lock() {
+        *file = Some((name, path.to_string()));
+    }
+}
+
 pub fn open_term() -> Result<TerminalC, io::Error> {
 
 
@@ -25,45 +43,143 @@ pub fn open_term() -> Result<TerminalC, io::Error> {
     Ok(terminal)
 }
 
-fn close_term(mut terminal: TerminalC) -> Result<(), io::Error> {
-    thread::sleep(Duration::from_millis(5000));
-
-    // restore terminal
+// puts the terminal back the way the shell expects it.
+// doesn't need the Terminal, so it can run from the panic hook too.
+fn restore_term() -> Result<(), io::Error> {
     disable_raw_mode()?;
     execute!(
-        terminal.backend_mut(),
+        io::stdout(),
         LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
+        DisableMouseCapture,
+        Show
+    )
+}
+
+fn close_term(mut terminal: TerminalC) -> Result<(), io::Error> {
+
+    // restore terminal
+    restore_term()?;
     terminal.show_cursor()?;
 
     Ok(())
 }
 
+// hands the terminal back to the shell and stops, like ctrl-z normally would.
+// picks up again once the shell continues the process.
+pub fn suspend(terminal: &mut TerminalC) -> Result<(), io::Error> {
+    restore_term()?;
+
+    // raw mode swallows the ctrl-z, so the signal has to be raised by hand.
+    unsafe {
+        libc::raise(libc::SIGTSTP);
+    }
+
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    // whatever the shell printed in the meantime is still on screen.
+    terminal.clear()
+}
+
+// writes out everything needed to chase down a crash, returning where it went.
+fn write_crash_report(info: &panic::PanicHookInfo) -> io::Result<PathBuf> {
+
+    let time = SystemTime::now().duration_since(UNIX_EPOCH).map(|t| t.as_secs()).unwrap_or(0);
+    let path = std::env::temp_dir().join(format!("chess-crash-{}.txt", time));
+
+    let position = match LAST_POSITION.try_lock() {
+        Ok(position) => position.clone(),
+        Err(_) => None
+    };
+
+    let file = match VARIANT_FILE.try_lock() {
+        Ok(file) => file.clone(),
+        Err(_) => None
+    };
+
+    let mut report = format!("chess {} crashed.\n\n{}\n\n", env!("CARGO_PKG_VERSION"), info);
+    match position {
+        Some((variant, fen)) => {
+            let flag = match file {
+                Some((name, path)) if name == variant => format!("--variant-file \"{}\" ", path),
+                _ if variant::find(variant).is_some() => format!("--variant \"{}\" ", variant),
+                _ => String::new()
+            };
+            report.push_str(&format!("variant: {}\nposition: {}\nreplay it with: chess {}--fen \"{}\"\n\n", variant, fen, flag, fen));
+        },
+        None => report.push_str("no game was in progress.\n\n")
+    }
+    report.push_str(&Backtrace::force_capture().to_string());
+
+    fs::write(&path, report)?;
+    Ok(path)
+}
+
+// makes sure a panic anywhere leaves the terminal usable and a report behind.
+// it's installed from the thread the screen is drawn on; a panic on any other one takes the whole app down with it,
+// since the screen would otherwise carry on over a terminal that's no longer set up for it.
+fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    let ui = thread::current().id();
+
+    panic::set_hook(Box::new(move |info| {
+        let _ = restore_term();
+        default_hook(info);
+
+        match write_crash_report(info) {
+            Ok(path) => eprintln!("a crash report was written to {}", path.display()),
+            Err(e) => eprintln!("couldn't write a crash report: {}", e)
+        }
+        if thread::current().id() != ui {
+            process::exit(101);
+        }
+    }));
+}
+
 // starts the terminal, and runs the event loop.
-pub fn start_terminal() -> Result<(), io::Error> {
+// a game to jump straight into can be passed in, skipping the menu.
+pub fn start_terminal(start: Option<Game>, peer: Option<Peer>) -> Result<(), io::Error> {
+    install_panic_hook();
     let mut terminal = open_term()?;
 
-    let res = draw(&mut terminal);
-    let res2 = events::start_event_loop(&mut terminal);
+    // everything runs on this thread; long work like engine searches gets its own.
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .enable_io()
+        .build()?;
+    let res = runtime.block_on(events::start_event_loop(&mut terminal, start, peer));
 
     close_term(terminal)?;
     res?;
-    res2?;
 
     Ok(())
 }
 
 
-pub fn draw(terminal: &mut TerminalC) -> Result<(), io::Error> {
+// draws whichever screen the app is on.
+pub fn draw(terminal: &mut TerminalC, app: &App) -> Result<(), io::Error> {
 
     terminal.draw(|f| {
         let size = f.size();
-        let block = Block::default()
-            .title("Block")
-            .borders(Borders::ALL);
-        f.render_widget(block, size);
+        let menu = menu_widget::get_rect().intersection(size);
+
+        match &app.screen {
+            Screen::MainMenu(index) => f.render_widget(menu_widget::main_menu(*index), menu),
+            Screen::NewGame(dialog) => f.render_widget(menu_widget::new_game(dialog), menu),
+            Screen::Settings(row) => f.render_widget(menu_widget::settings(&app.settings, *row), menu),
+            Screen::InGame(user) | Screen::GameOver(user) => {
+                f.render_widget(user, user.get_rect().intersection(size));
+                f.render_widget(user.status_bar(), user.get_status_rect().intersection(size));
+                if user.game.variant.has_pockets() {
+                    f.render_widget(user.pockets(), user.get_pocket_rect().intersection(size));
+                }
+                if let Some(partner) = &user.partner {
+                    let [board, status, pockets] = user.get_partner_rects();
+                    f.render_widget(&**partner, board.intersection(size));
+                    f.render_widget(partner.status_bar(), status.intersection(size));
+                    f.render_widget(partner.pockets(), pockets.intersection(size));
+                }
+            }
+        }
     })?;
-    // restore terminal
     Ok(())
 }
@@ -0,0 +1,105 @@
+use std::{sync::mpsc, thread};
+use crate::{
+    display::app::{App, Screen},
+    engine::time::{Limits, Stop},
+    fen::to_fen,
+    game::Game,
+    uci::client::{ClientError, Info}
+};
+
+use super::{UserState, computer};
+
+
+// the external engine looking at the game as it goes along, with what it thinks shown under the board.
+// it starts again on every move. it's a second copy of the engine, so it goes on while the other one plays.
+
+pub struct Analysis {
+    // the position it's looking at, so anything it says about older ones can be ignored.
+    pub position: String,
+    pub stop: Stop,
+    pub info: Option<Info>
+}
+
+impl Analysis {
+    fn new() -> Analysis {
+        Analysis { position: String::new(), stop: Stop::default(), info: None }
+    }
+}
+
+// turns it on or off. it isn't allowed where it would show pieces the player can't see, or help against someone else.
+pub fn toggle(user: &mut UserState) {
+    if let Some(analysis) = user.analysis.take() {
+        analysis.stop.stop();
+        user.message = Some("Analysis off.".to_string());
+        return;
+    }
+    user.message = Some(match computer::engine_name() {
+        None => "There's no engine to analyse with. Start with --engine <path> to use one.".to_string(),
+        Some(_) if user.peer.is_some() || user.game.variant.hides() => "Analysis isn't allowed in this game.".to_string(),
+        Some(name) => {
+            user.analysis = Some(Analysis::new());
+            format!("{} is analysing. Press e again to stop.", name)
+        }
+    });
+}
+
+// the position to start looking at, if it's moved on since the last one.
+pub fn wanted(user: &mut UserState) -> Option<(Game, Stop)> {
+
+    // it follows whichever Bughouse board is showing.
+    if let Some(analysis) = user.partner.as_mut().and_then(|partner| partner.analysis.take()) {
+        analysis.stop.stop();
+        user.analysis = Some(Analysis::new());
+    }
+
+    let game = &user.game;
+    let analysis = user.analysis.as_mut()?;
+    let position = to_fen(game);
+    if analysis.position == position {
+        return None;
+    }
+
+    analysis.stop.stop();
+    analysis.stop = Stop::default();
+    analysis.position = position;
+    analysis.info = None;
+
+    match game.outcome.is_none() {
+        true => Some((game.clone(), analysis.stop.clone())),
+        false => None
+    }
+}
+
+// runs on its own thread until it's stopped, handing on what the engine thinks as it goes.
+pub fn analyse(game: Game, stop: Stop) -> impl Iterator<Item = (String, Result<Info, ClientError>)> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let position = to_fen(&game);
+        let limits = Limits { stop, ..Limits::default() };
+        let result = computer::with_engine(true, |client| client.go(&game, &limits, |info| {
+            let _ = sender.send((position.clone(), Ok(info)));
+        }));
+        if let Err(e) = result {
+            let _ = sender.send((position, Err(e)));
+        }
+    });
+    receiver.into_iter()
+}
+
+pub fn event((position, info): (String, Result<Info, ClientError>), app: &mut App) {
+    let user = match &mut app.screen {
+        Screen::InGame(user) => user,
+        _ => return
+    };
+    let analysis = match &mut user.analysis {
+        Some(analysis) if analysis.position == position => analysis,
+        _ => return
+    };
+    match info {
+        Ok(info) => analysis.info = Some(info),
+        Err(e) => {
+            user.analysis = None;
+            user.message = Some(format!("{}.", e));
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use std::{sync::{Mutex, atomic::{AtomicUsize, Ordering}}, thread, time::{Duration, Instant}};
+use crate::{
+    display::app::{App, Screen},
+    engine::{self, Play, search::Options, table::Table, time::Limits},
+    fen::to_fen,
+    game::{Game, side},
+    uci::client::{Client, ClientError}
+};
+
+use super::{UserState, key_press};
+
+
+// a moment's pause before the computer moves, so its moves can be followed.
+const PAUSE: Duration = Duration::from_millis(400);
+// how many moves ahead it looks, when there's no clock.
+const DEPTH: u32 = 3;
+// how long an external engine gets, when there's no clock. it can look a long way ahead in that time.
+const ENGINE_TIME: Duration = Duration::from_secs(1);
+
+// how many megabytes the computer remembers positions in, which --hash can change.
+static HASH_MB: AtomicUsize = AtomicUsize::new(Table::DEFAULT_MB);
+
+// kept from one move to the next, along with the variant it was filled in for.
+// both Bughouse boards share it, and take turns.
+static TABLE: Mutex<Option<(&'static str, Table)>> = Mutex::new(None);
+
+// where an external engine is, and the options to start it with.
+type EngineCommand = (String, Vec<(String, String)>);
+
+// the one --engine gave, if any, and the options --engine-option gave it.
+static ENGINE: Mutex<Option<EngineCommand>> = Mutex::new(None);
+// started the first time it's needed and kept after that, since engines can take a while to start.
+// the one playing and the one analysing are run separately, so neither has to wait for the other.
+// both Bughouse boards share the one playing, and take turns.
+static CLIENTS: [Mutex<Option<Client>>; 2] = [const { Mutex::new(None) }; 2];
+
+// counts the games that have been dropped, so a move thought up for one of them isn't played in the next.
+static GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+pub fn set_hash_size(mb: usize) {
+    HASH_MB.store(mb, Ordering::Relaxed);
+}
+
+pub fn set_engine(path: String, options: Vec<(String, String)>) {
+    *ENGINE.lock().unwrap_or_else(|e| e.into_inner()) = Some((path, options));
+}
+
+// what to call the external engine in the menus, going by where it is, so it doesn't have to be started to ask.
+pub fn engine_name() -> Option<String> {
+    let engine = ENGINE.lock().unwrap_or_else(|e| e.into_inner());
+    engine.as_ref().map(|(path, _)| path.rsplit('/').next().unwrap_or(path).to_string())
+}
+
+// has the external engine do something, either playing or analysing, starting it first if it isn't running.
+// if it goes wrong the engine is shut down, so it starts afresh next time.
+pub fn with_engine<T>(analysing: bool, work: impl FnOnce(&mut Client) -> Result<T, ClientError>) -> Result<T, ClientError> {
+    let mut client = CLIENTS[analysing as usize].lock().unwrap_or_else(|e| e.into_inner());
+    if client.is_none() {
+        let (path, options) = ENGINE.lock().unwrap_or_else(|e| e.into_inner()).clone()
+            .ok_or(ClientError::Spawn(std::io::ErrorKind::NotFound.into()))?;
+        *client = Some(Client::start(&path, &options)?);
+    }
+    let result = work(client.as_mut().unwrap());
+    if result.is_err() {
+        *client = None;
+    }
+    result
+}
+
+// what the computer played, and in which game and position, since the game may have moved on while it thought.
+pub struct Thought {
+    generation: usize,
+    position: String,
+    play: Result<Option<Play>, ClientError>
+}
+
+fn wants(user: &mut UserState) -> Option<(usize, Game, Limits, bool)> {
+    let game = &user.game;
+    if user.thinking.is_some() || game.outcome.is_some() || !user.computer[side(game.turn_white)] {
+        return None;
+    }
+
+    // with a clock running it takes as long as it can spare, and otherwise looks a fixed way ahead.
+    let engine = user.engine[side(game.turn_white)];
+    let limits = match (&game.clock, engine) {
+        (Some(clock), _) => Limits::clock(clock),
+        (None, false) => Limits::depth(DEPTH),
+        (None, true) => Limits { movetime: Some(ENGINE_TIME), ..Limits::default() }
+    };
+    user.thinking = Some(limits.stop.clone());
+    Some((user.board_number, game.clone(), limits, engine))
+}
+
+// the boards where it's the computer's turn and it isn't thinking yet, which it now is,
+// and whether it's the external engine that's to move there.
+pub fn wanted(user: &mut UserState) -> Vec<(usize, Game, Limits, bool)> {
+    let mut wanted: Vec<(usize, Game, Limits, bool)> = wants(user).into_iter().collect();
+    if let Some(partner) = &mut user.partner {
+        wanted.extend(wants(partner));
+    }
+    wanted
+}
+
+// which game is being played now, to tag what the computer is asked with.
+pub fn generation() -> usize {
+    GENERATION.load(Ordering::Relaxed)
+}
+
+// calls off any thinking or analysis on either board, once nobody is waiting for it.
+// the game is over with, so anything still on its way back from it gets dropped.
+pub fn stop(user: &UserState) {
+    GENERATION.fetch_add(1, Ordering::Relaxed);
+    for board in [Some(user), user.partner.as_deref()].into_iter().flatten() {
+        if let Some(stop) = &board.thinking {
+            stop.stop();
+        }
+        if let Some(analysis) = &board.analysis {
+            analysis.stop.stop();
+        }
+    }
+}
+
+// runs on its own thread, so the board can still be looked around while it does.
+pub fn think(generation: usize, game: Game, limits: Limits, engine: bool) -> Thought {
+    let start = Instant::now();
+    let play = match engine {
+        true => with_engine(false, |client| client.go(&game, &limits, |_| ())),
+        false => Ok(own_move(&game, &limits))
+    };
+    thread::sleep(PAUSE.saturating_sub(start.elapsed()));
+    Thought { generation, position: to_fen(&game), play }
+}
+
+fn own_move(game: &Game, limits: &Limits) -> Option<Play> {
+    let mut kept = TABLE.lock().unwrap_or_else(|e| e.into_inner());
+    // the same pieces in the same places can be worth something else under other rules.
+    if kept.as_ref().is_none_or(|(variant, _)| *variant != game.variant.name()) {
+        *kept = Some((game.variant.name(), Table::new(HASH_MB.load(Ordering::Relaxed))));
+    }
+    let (_, table) = kept.as_mut().unwrap();
+    engine::search(game, limits, table, Options::default()).play
+}
+
+pub fn event(number: usize, thought: Thought, app: &mut App) {
+    let user = match &mut app.screen {
+        Screen::InGame(user) if thought.generation == generation() => user,
+        _ => return
+    };
+    let board = match (user.board_number == number, user.partner.as_deref_mut()) {
+        (true, _) => user,
+        (false, Some(partner)) => partner,
+        (false, None) => return
+    };
+    board.thinking = None;
+
+    // a piece arriving from the other board changes what's best, so it'll think again.
+    if to_fen(&board.game) != thought.position {
+        return;
+    }
+    match thought.play {
+        Ok(Some(play)) => if play.play(&mut board.game).is_ok() {
+            key_press::after_move(board);
+        },
+        Ok(None) => (),
+        // it's taken over by the computer's own search, rather than leave the game stuck.
+        Err(e) => {
+            board.engine = [false, false];
+            board.message = Some(format!("{}. The computer took over.", e));
+        }
+    }
+}
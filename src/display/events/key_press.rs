@@ -1,12 +1,20 @@
 
 use std::time::Instant;
 use crossterm::event::{KeyEvent, KeyCode};
-use crate::{board::{read_board, move_piece}, types::Space, check::is_checkmated, display::events::BREAK_LOOP};
+use crate::{
+    board::{Loc, read_board},
+    game::{side, team_name},
+    net::Message,
+    pocket,
+    types::{IllegalMove, Movement, Promotion, Space},
+    umpire::{self, Announcement}
+};
 
-use super::UserState;
+use super::{UserState, analysis};
 
 
-enum Action {
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Action {
     Up,
     Left,
     Down,
@@ -14,19 +22,9 @@ enum Action {
     Select,
 }
 
-pub fn event(e: KeyEvent, user: &mut UserState) {
-    let key = e.code;
-
-    // quit the loop
-    if key == KeyCode::Esc {
-        println!("quitting program");
-        unsafe{
-            crate::display::events::BREAK_LOOP = true;
-        }
-    }
-
+// the key bindings shared by the board and the menus.
+pub fn action(key: KeyCode) -> Option<Action> {
 
-    // other stuff
     use Action::*;
 
     let action = match key {
@@ -37,7 +35,7 @@ pub fn event(e: KeyEvent, user: &mut UserState) {
             's' | 'j' => Action::Down,
             'd' | 'l' => Action::Right,
             ' ' => Select,
-            _ => {return;}
+            _ => {return None;}
 
         },
         KeyCode::Enter => Select,
@@ -46,49 +44,230 @@ pub fn event(e: KeyEvent, user: &mut UserState) {
         KeyCode::Down => Down,
         KeyCode::Right => Right,
 
-        _ => {return;}
+        _ => {return None;}
 
     };
+    Some(action)
+}
+
+pub fn event(e: KeyEvent, user: &mut UserState) {
+
+    // the next player is ready to look.
+    if user.hidden {
+        user.hidden = false;
+        return;
+    }
+
+    let turn_white = user.game.turn_white;
+    take_key(e, user);
+
+    // someone just moved, so the board goes blank before it gets passed over.
+    if user.game.turn_white != turn_white && user.game.variant.hides() && user.game.outcome.is_none() && user.hot_seat() {
+        user.hidden = true;
+        user.selected = None;
+        user.message = None;
+    }
+}
+
+fn take_key(e: KeyEvent, user: &mut UserState) {
+
+    if e.code == KeyCode::Tab {
+        switch_boards(user);
+        return;
+    }
+
+    // a pawn waiting on the far end takes the letter of what it becomes, and anything else calls the move off.
+    if let Some((fromc, toc)) = user.promoting.take() {
+        user.message = None;
+        let promotion = match e.code {
+            KeyCode::Char(letter) => Promotion::from_letter(letter),
+            _ => None
+        };
+        let promotions = user.game.variant.promotions_at(&user.game.board, fromc, toc);
+        if let Some(promotion) = promotion.filter(|promotion| promotions.contains(promotion)) {
+            play(user, fromc, toc, promotion);
+        }
+        return;
+    }
+
+    if e.code == KeyCode::Char('e') {
+        analysis::toggle(user);
+        return;
+    }
+
+    // the letter of a piece picks it out of the pocket, in variants that have them.
+    if let KeyCode::Char(letter) = e.code {
+        let kind = pocket::KINDS.iter().position(|kind| *kind == letter.to_ascii_uppercase());
+        if let (Some(kind), true) = (kind, user.game.variant.has_pockets()) {
+            if !waiting(user) {
+                choose_drop(kind, user);
+            }
+            return;
+        }
+    }
+
+    match action(e.code) {
+        Some(Action::Select) if waiting(user) => (),
+        Some(action) => act(action, user),
+        None => ()
+    }
+}
+
+// plays the move and tells the other player about it, or explains what went wrong.
+// the umpire never says why, since the reason could give the other side's pieces away.
+fn play(user: &mut UserState, fromc: Loc, toc: Loc, promotion: Promotion) -> bool {
+    let promotes = !user.game.variant.promotions_at(&user.game.board, fromc, toc).is_empty();
+    match user.game.play_promoting(fromc, toc, promotion) {
+        Ok(()) => {
+            after_move(user);
+            if let Some(peer) = &mut user.peer {
+                peer.send(Message::Move(fromc, toc, promotes.then_some(promotion)));
+            }
+            true
+        },
+        Err(reason) => {
+            let umpired = user.game.variant.umpired();
+            user.message = Some(match umpired {
+                true => Announcement::Illegal.to_string(),
+                false => reason.to_string()
+            });
+            // the other player hears it too, without finding out what was tried.
+            if let (Some(peer), true) = (&mut user.peer, umpired) {
+                peer.send(Message::Illegal);
+            }
+            false
+        }
+    }
+}
+
+// sets off the explosion and lets the umpire have their say, after a move by either player.
+pub fn after_move(user: &mut UserState) {
+    if user.game.variant.explodes() && user.game.last_capture.is_some() {
+        user.explosion = Some(Instant::now());
+    }
+    if user.game.variant.umpired() {
+        user.umpire = umpire::announce(&user.game);
+    }
+}
+
+// true if it's the other player's or the computer's turn, in which case they're told to wait.
+fn waiting(user: &mut UserState) -> bool {
+    let turn_white = user.game.turn_white;
+    let waiting = user.computer[side(turn_white)] || user.peer.as_ref().is_some_and(|peer| peer.is_white == turn_white);
+    if waiting {
+        user.message = Some(format!("Waiting for {} to move.", team_name(turn_white)));
+    }
+    waiting
+}
+
+// looks at the other board in Bughouse, from the partner's side.
+fn switch_boards(user: &mut UserState) {
+    if let Some(mut partner) = user.partner.take() {
+        std::mem::swap(user, &mut partner);
+        partner.selected = None;
+        partner.dropping = None;
+        partner.promoting = None;
+        partner.cursor_blink = false;
+        user.partner = Some(partner);
+    }
+}
+
+// asks which piece a pawn should become, if the move is allowed and there's more than one choice.
+fn ask_promotion(user: &mut UserState, fromc: Loc, toc: Loc) -> bool {
+    let game = &user.game;
+    let promotions = game.variant.promotions_at(&game.board, fromc, toc);
+    let legal = game.variant.moves(&game.board, fromc).iter()
+        .any(|movement| movement.to == toc && !matches!(movement.relation, Movement::Check | Movement::Blocked));
+
+    if promotions.len() < 2 || !legal {
+        return false;
+    }
 
-    act(action, user);
+    let letters: Vec<String> = promotions.iter().map(|promotion| promotion.letter().to_string()).collect();
+    user.message = Some(format!("Promote to which piece? ({})", letters.join("/")));
+    user.promoting = Some((fromc, toc));
+    true
+}
+
+fn choose_drop(kind: usize, user: &mut UserState) {
+
+    user.selected = None;
+
+    // picking the same piece again puts it back.
+    if user.dropping == Some(kind) {
+        user.dropping = None;
+        user.message = None;
+    } else if user.game.pockets[side(user.game.turn_white)][kind] == 0 {
+        user.dropping = None;
+        user.message = Some(IllegalMove::NothingToDrop.to_string());
+    } else {
+        user.dropping = Some(kind);
+        user.message = Some(format!("Pick a square to drop the {} on.", pocket::NAMES[kind]));
+    }
 }
 
 
 fn act(action: Action, user: &mut UserState) {
+    let (width, height) = (user.game.board.width, user.game.board.height);
     let cursor = &mut user.cursor;
 
     user.cursor_blink = true;
     user.blink_timer = Instant::now();
 
     use Action::*;
+
+    // the arrows follow the screen, not the board.
+    let action = match (user.flipped, action) {
+        (true, Up) => Down,
+        (true, Down) => Up,
+        (true, Left) => Right,
+        (true, Right) => Left,
+        (_, action) => action
+    };
+
     match action {
         Up => {
             if cursor[1] != 1 {
-                cursor[1] = cursor[1] - 1;
+                cursor[1] -= 1;
             }
         },
         Left => {
             if cursor[0] != 1 {
-                cursor[0] = cursor[0] - 1;
+                cursor[0] -= 1;
             }
         },
         Down => {
-            if cursor[1] != 8 {
-                cursor[1] = cursor[1] + 1;
+            if cursor[1] != height {
+                cursor[1] += 1;
             }
         },
         Right => {
-            if cursor[0] != 8 {
-                cursor[0] = cursor[0] + 1;
+            if cursor[0] != width {
+                cursor[0] += 1;
             }
         },
         Select => {
 
             let cursor = user.cursor;
             let selection = user.selected;
+            let turn_white = user.game.turn_white;
+            user.message = None;
+
+            if let Some(kind) = user.dropping.take() {
+                match user.game.drop(kind, cursor) {
+                    Ok(()) => {
+                        after_move(user);
+                        if let Some(peer) = &mut user.peer {
+                            peer.send(Message::Drop(kind, cursor));
+                        }
+                    },
+                    Err(reason) => user.message = Some(reason.to_string())
+                }
+                return;
+            }
 
             // gets the space at the cursor location
-            let cursor_space = match read_board(&user.board, cursor) {
+            let cursor_space = match read_board(&user.game.board, cursor) {
                 Some(s) => s,
                 None => return
             };
@@ -99,39 +278,30 @@ fn act(action: Action, user: &mut UserState) {
 
                 Some(select) => {
 
-                    let select_piece = match read_board(&user.board, select) {
-                        Some(p) => p,
-                        None => return
-                    };
-
-                    if select_piece == Space::Open {
-                        user.selected = Some(cursor);
+                    // picking another of your own pieces switches the selection over to it,
+                    // unless it's a king castling onto its rook.
+                    if select != cursor && cursor_space != Space::Open && cursor_space.is_white() == turn_white {
+                        user.selected = match play(user, select, cursor, user.game.variant.promotions()[0]) {
+                            true => None,
+                            false => Some(cursor)
+                        };
+                        user.message = None;
                         return;
                     }
 
-                    if select != cursor &&
-                        move_piece(&mut user.board, select, select_piece, cursor, cursor_space) {
-
-                        let string = match user.turn_white {
-                            true => "White",
-                            false => "Black"
-                        };
-
-                        user.turn_white = !user.turn_white;
-                        if is_checkmated(&user.board, user.turn_white) {
-
-                            println!("\n\n\n\n\n{} has won!", string);
-                            unsafe {BREAK_LOOP = true;}
-                        }
+                    if select != cursor && !ask_promotion(user, select, cursor) {
+                        play(user, select, cursor, user.game.promotion(select, cursor));
                     }
 
                     user.selected = None;
-                    return;
                 },
                 None => {
-                    if cursor_space == Space::Open || cursor_space.is_white() == user.turn_white {
+                    if cursor_space == Space::Open {
+                        user.message = Some(IllegalMove::NoPiece.to_string());
+                    } else if cursor_space.is_white() != turn_white {
+                        user.message = Some(IllegalMove::WrongSide.to_string());
+                    } else {
                         user.selected = Some(cursor);
-                        return;
                     }
                 }
             }
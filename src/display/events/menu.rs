@@ -0,0 +1,65 @@
+
+use crossterm::event::{KeyEvent, KeyCode};
+use crate::display::app::{App, Screen, NewGameDialog, Settings, step};
+
+use super::{computer, key_press::{self, Action::*}};
+
+
+// routes key presses to whichever screen is showing.
+pub fn event(e: KeyEvent, app: &mut App) {
+    let key = e.code;
+    let action = key_press::action(key);
+
+    match &mut app.screen {
+
+        Screen::MainMenu(index) => match (key, action) {
+            (KeyCode::Esc | KeyCode::Char('q'), _) => app.running = false,
+            (_, Some(Up)) => *index = step(*index, App::MAIN_MENU.len(), false),
+            (_, Some(Down)) => *index = step(*index, App::MAIN_MENU.len(), true),
+            (_, Some(Select)) => match *index {
+                0 => app.screen = Screen::NewGame(NewGameDialog::default()),
+                1 => app.screen = Screen::Settings(0),
+                _ => app.running = false
+            },
+            _ => ()
+        },
+
+        Screen::NewGame(dialog) => match (key, action) {
+            (KeyCode::Esc, _) => app.screen = Screen::MainMenu(0),
+            (_, Some(Up)) => dialog.field = step(dialog.field, NewGameDialog::FIELDS, false),
+            (_, Some(Down)) => dialog.field = step(dialog.field, NewGameDialog::FIELDS, true),
+            (KeyCode::Char(c @ '0'..='9'), _) if dialog.field == NewGameDialog::CHESS960_FIELD => {
+                dialog.type_digit(c as u16 - '0' as u16);
+            },
+            (KeyCode::Backspace, _) if dialog.field == NewGameDialog::CHESS960_FIELD => dialog.erase_digit(),
+            (_, Some(Left)) => dialog.cycle(false),
+            (_, Some(Right)) => dialog.cycle(true),
+            (_, Some(Select)) => if dialog.field == NewGameDialog::FIELDS - 1 {
+                app.screen = Screen::InGame(dialog.start(app.settings));
+            } else {
+                dialog.cycle(true);
+            },
+            _ => ()
+        },
+
+        Screen::Settings(row) => match (key, action) {
+            (KeyCode::Esc, _) => app.screen = Screen::MainMenu(1),
+            (_, Some(Up)) => *row = step(*row, Settings::COUNT, false),
+            (_, Some(Down)) => *row = step(*row, Settings::COUNT, true),
+            (_, Some(Left | Right | Select)) => app.settings.toggle(*row),
+            _ => ()
+        },
+
+        // leaving a game part way through drops it.
+        Screen::InGame(user) => match key {
+            KeyCode::Esc => {
+                computer::stop(user);
+                app.screen = Screen::MainMenu(0);
+            },
+            _ => key_press::event(e, user)
+        },
+
+        // any key once the result has been read.
+        Screen::GameOver(_) => app.screen = Screen::MainMenu(0)
+    }
+}
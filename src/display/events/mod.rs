@@ -1,12 +1,18 @@
+pub mod analysis;
+pub mod computer;
 mod key_press;
+mod menu;
+mod remote;
 mod resize;
 
 use std::time::{Duration, Instant};
-use crossterm::event::{poll, read, Event};
-use super::dynamic::TerminalC;
-use crate::{board::{Board, Loc}, check::is_checkmated};
+use crossterm::event::{Event, EventStream, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use futures::StreamExt;
+use tokio::{signal::unix::{signal, SignalKind}, sync::mpsc, time::{interval, MissedTickBehavior}};
+use super::{app::{App, Screen, Settings}, dynamic::{self, TerminalC}};
+use crate::{board::Loc, engine::time::Stop, game::Game, net::Peer, umpire::Announcement};
 
-pub static mut BREAK_LOOP: bool = false;
+pub use analysis::Analysis;
 
 
 // positions are stored in IN GAME location,
@@ -16,54 +22,167 @@ pub struct UserState {
 
     pub cursor: Loc,
     pub selected: Option<Loc>,
+    // the kind of piece picked out of the pocket to drop, in Crazyhouse.
+    pub dropping: Option<usize>,
+    // a pawn move waiting on which piece it should promote to.
+    pub promoting: Option<(Loc, Loc)>,
+    // when the last capture went off, in Atomic.
+    pub explosion: Option<Instant>,
+    // the board is blanked while a hot seat game is handed over, in variants where each side sees a different board.
+    pub hidden: bool,
+    // what the umpire said about the last move, in variants that have one.
+    pub umpire: Vec<Announcement>,
+    // the other player, when they're on another computer.
+    pub peer: Option<Peer>,
+    // which sides the computer plays, and while it's busy working out a move, how to stop it.
+    pub computer: [bool; 2],
+    // which of those sides the external engine plays, instead of the computer's own search.
+    pub engine: [bool; 2],
+    pub thinking: Option<Stop>,
+    // the external engine's view of the game, while it's turned on.
+    pub analysis: Option<Analysis>,
+    // the other board in Bughouse, which tab swaps in. 'board_number' tells the two apart.
+    pub partner: Option<Box<UserState>>,
+    pub board_number: usize,
     pub cursor_blink: bool,
     pub blink_timer: Instant,
-    pub turn_white: bool,
-    pub board: Board
+    // true when black is at the bottom of the board.
+    pub flipped: bool,
+    pub settings: Settings,
+    pub game: Game,
 
-}
+    // feedback about the last thing the player tried, shown in the status bar.
+    pub message: Option<String>
 
+}
 
-// routes all events from the terminal to each module.
-pub fn start_event_loop(terminal: &mut TerminalC) -> crossterm::Result<()> {
+impl UserState {
+
+    pub const BLINK: Duration = Duration::from_millis(500);
+    pub const EXPLOSION: Duration = Duration::from_millis(600);
+
+    pub fn new(game: Game, flipped: bool, settings: Settings) -> UserState {
+        UserState {
+            cursor: if flipped {[game.board.width, game.board.height]} else {[1, 1]},
+            selected: None,
+            dropping: None,
+            promoting: None,
+            explosion: None,
+            hidden: false,
+            umpire: Vec::new(),
+            peer: None,
+            computer: [false, false],
+            engine: [false, false],
+            thinking: None,
+            analysis: None,
+            partner: None,
+            board_number: 0,
+            cursor_blink: true,
+            blink_timer: Instant::now(),
+            flipped,
+            settings,
+            game,
+            message: None
+        }
+    }
 
-    let mut user_state = UserState {
-        cursor: [1, 1],
-        selected: None,
-        cursor_blink: true,
-        blink_timer: Instant::now(),
-        turn_white: true,
-        board: crate::board::STANDARD_BOARD
+    // the side whose pieces the board is shown from, in variants where each side sees something different:
+    // the one person here when the other side is elsewhere or the computer, or whoever's turn it is when two share the screen.
+    pub fn viewer(&self) -> bool {
+        match (&self.peer, self.computer) {
+            (Some(peer), _) => !peer.is_white,
+            (None, [true, false]) => false,
+            (None, [false, true]) => true,
+            (None, _) => self.game.turn_white
+        }
+    }
 
-    };
+    // whether two people are taking turns at this screen, so it has to be handed over between moves.
+    pub fn hot_seat(&self) -> bool {
+        self.peer.is_none() && self.computer == [false, false]
+    }
+}
 
-    'event: loop {
-        // `read()` blocks until an `Event` is available
 
-        if poll(Duration::from_millis(1))? {
-            match read()? {
-                Event::Key(event) => key_press::event(event, &mut user_state),
-                Event::Resize(width, height) => resize::event(width, height),
-                _ => ()
-            }
-        }
+// routes all events from the terminal, the timers and any background work to each module,
+// and redraws whenever one of them changed something.
+pub async fn start_event_loop(terminal: &mut TerminalC, start: Option<Game>, peer: Option<Peer>) -> crossterm::Result<()> {
 
-        let cb = user_state.cursor_blink.clone();
+    let (sender, mut tasks) = mpsc::unbounded_channel();
+    let mut app = App::new(sender);
 
-        // cursor blink manager
-        if user_state.blink_timer.elapsed() >= Duration::from_millis(500) {
-            user_state.cursor_blink = !cb;
-            user_state.blink_timer = Instant::now();
+    if let Some(game) = start {
+        // whoever is playing here sits on their own side of the board.
+        let mut user = UserState::new(game, peer.as_ref().is_some_and(|peer| peer.is_white), app.settings);
+        if let Some(mut peer) = peer {
+            if let Some(incoming) = peer.incoming() {
+                app.listen(incoming, remote::event);
+            }
+            user.peer = Some(peer);
         }
+        app.screen = Screen::InGame(user);
+    }
 
-        terminal.draw(|f| {
-            f.render_widget(&user_state, UserState::get_rect());
-
-        })?;
+    let mut events = EventStream::new();
+    let mut ticker = interval(App::TICK);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+    // raw mode turns ctrl-c into a key press, but these can still come from outside.
+    let mut interrupt = signal(SignalKind::interrupt())?;
+    let mut terminate = signal(SignalKind::terminate())?;
+
+    dynamic::draw(terminal, &app)?;
+
+    while app.running {
+
+        let redraw = tokio::select! {
+            event = events.next() => match event {
+                Some(Ok(Event::Key(event))) if event.kind == KeyEventKind::Release => false,
+                Some(Ok(Event::Key(event))) if is_ctrl(event, 'c') => {
+                    app.running = false;
+                    false
+                },
+                Some(Ok(Event::Key(event))) if is_ctrl(event, 'z') => {
+                    dynamic::suspend(terminal)?;
+                    true
+                },
+                Some(Ok(Event::Key(event))) => {
+                    menu::event(event, &mut app);
+                    true
+                },
+                Some(Ok(Event::Resize(width, height))) => {
+                    resize::event(width, height);
+                    true
+                },
+                Some(Ok(_)) => false,
+                Some(Err(e)) => return Err(e),
+                // the terminal has gone away.
+                None => break
+            },
+            _ = ticker.tick() => app.update(),
+            _ = interrupt.recv() => {
+                app.running = false;
+                false
+            },
+            _ = terminate.recv() => {
+                app.running = false;
+                false
+            },
+            Some(task) = tasks.recv() => {
+                task(&mut app);
+                true
+            }
+        };
 
-        if unsafe {BREAK_LOOP} {
-            break 'event;
+        if redraw {
+            app.update();
+            dynamic::draw(terminal, &app)?;
+            dynamic::record_position(app.position());
         }
     }
     Ok(())
 }
+
+fn is_ctrl(event: KeyEvent, letter: char) -> bool {
+    event.modifiers.contains(KeyModifiers::CONTROL) && event.code == KeyCode::Char(letter)
+}
@@ -0,0 +1,43 @@
+use crate::{net::{Incoming, Message}, umpire::Announcement};
+use crate::display::app::{App, Screen};
+
+use super::key_press;
+
+
+// moves from the other player, when they're on another computer.
+pub fn event(incoming: Incoming, app: &mut App) {
+    let user = match &mut app.screen {
+        Screen::InGame(user) => user,
+        _ => return
+    };
+
+    let played = match incoming {
+        Incoming::Message(Message::Move(fromc, toc, promotion)) => {
+            let promotion = promotion.unwrap_or(user.game.promotion(fromc, toc));
+            user.game.play_promoting(fromc, toc, promotion)
+        },
+        Incoming::Message(Message::Drop(kind, toc)) => user.game.drop(kind, toc),
+        // they tried something that wasn't allowed, and it's still their turn.
+        Incoming::Message(Message::Illegal) => {
+            user.umpire = vec![Announcement::Illegal];
+            return;
+        },
+        Incoming::Garbled(line) => {
+            user.message = Some(format!("The other player sent something that isn't a move: {}", line));
+            return;
+        },
+        Incoming::Left => {
+            user.message = Some("The other player has left the game.".to_string());
+            return;
+        }
+    };
+
+    match played {
+        Ok(()) => {
+            user.message = None;
+            key_press::after_move(user);
+        },
+        // both sides should be running the same rules, so this means they've fallen out of step.
+        Err(_) => user.message = Some("The other player sent a move that can't be played here.".to_string())
+    }
+}
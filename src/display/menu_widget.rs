@@ -0,0 +1,73 @@
+
+
+
+use super::app::{App, NewGameDialog, Settings};
+use ratatui::{
+    layout::Rect,
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+    style::{Style, Color, Modifier}
+};
+
+// menus sit where the board would be.
+pub fn get_rect() -> Rect {
+    Rect { x: 5, y: 5, width: 44, height: 14 }
+}
+
+fn entry(text: String, highlighted: bool) -> Spans<'static> {
+    match highlighted {
+        true => Spans::from(Span::styled(format!("> {}", text),
+            Style::default().bg(Color::Rgb(23,74,255)).add_modifier(Modifier::BOLD))),
+        false => Spans::from(format!("  {}", text))
+    }
+}
+
+fn hint(text: &'static str) -> Spans<'static> {
+    Spans::from(Span::styled(text, Style::default().fg(Color::Rgb(120,120,120))))
+}
+
+fn framed(title: &'static str, lines: Vec<Spans<'static>>) -> Paragraph<'static> {
+    Paragraph::new(lines)
+        .block(Block::default().title(title).borders(Borders::ALL))
+}
+
+pub fn main_menu(index: usize) -> Paragraph<'static> {
+
+    let mut lines = vec![Spans::from("")];
+    for (i, name) in App::MAIN_MENU.iter().enumerate() {
+        lines.push(entry(name.to_string(), i == index));
+    }
+    lines.push(Spans::from(""));
+    lines.push(hint("Up/Down to choose, Enter to confirm"));
+
+    framed("Chess", lines)
+}
+
+pub fn new_game(dialog: &NewGameDialog) -> Paragraph<'static> {
+
+    let mut lines = vec![Spans::from("")];
+    for (i, (label, value)) in dialog.rows().into_iter().enumerate() {
+        lines.push(entry(format!("{:<10}< {} >", label, value), i == dialog.field));
+    }
+    lines.push(Spans::from(""));
+    lines.push(entry("[ Start game ]".to_string(), dialog.field == NewGameDialog::FIELDS - 1));
+    lines.push(Spans::from(""));
+    lines.push(hint(match dialog.field {
+        NewGameDialog::CHESS960_FIELD => "Type a number, or Left/Right to change",
+        _ => "Left/Right to change, Esc to go back"
+    }));
+
+    framed("New game", lines)
+}
+
+pub fn settings(settings: &Settings, row: usize) -> Paragraph<'static> {
+
+    let mut lines = vec![Spans::from("")];
+    for (i, (label, on)) in settings.rows().into_iter().enumerate() {
+        lines.push(entry(format!("{:<27}{}", label, if on {"on"} else {"off"}), i == row));
+    }
+    lines.push(Spans::from(""));
+    lines.push(hint("Enter to toggle, Esc to go back"));
+
+    framed("Settings", lines)
+}
@@ -1,4 +1,9 @@
+pub mod app;
 pub mod dynamic;
 pub mod board_widget;
-pub use board_widget as widget;
-mod events;
+pub mod status_widget;
+pub mod menu_widget;
+pub mod pocket_widget;
+pub mod events;
+
+pub use events::computer::{set_engine, set_hash_size};
@@ -0,0 +1,58 @@
+use super::events::UserState;
+use crate::{game::{side, team_name}, pocket};
+use ratatui::{
+    layout::Rect,
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+    style::{Style, Color, Modifier}
+};
+
+impl UserState {
+    // sits to the right of the board.
+    pub fn get_pocket_rect(&self) -> Rect {
+        let board = self.get_rect();
+        Rect { x: board.right() + 2, y: board.y, width: 24, height: 5 }
+    }
+
+    // the other board in Bughouse goes to the right of the pockets, with its status and pockets underneath.
+    pub fn get_partner_rects(&self) -> [Rect; 3] {
+        let board = Rect { x: self.get_pocket_rect().right() + 2, ..self.get_rect() };
+        let status = Rect { x: board.x, ..self.get_status_rect() };
+        let pockets = Rect { x: board.x, y: status.bottom() + 1, ..self.get_pocket_rect() };
+        [board, status, pockets]
+    }
+
+    // what each side has captured and can drop, with the piece picked to drop picked out.
+    pub fn pockets(&self) -> Paragraph<'_> {
+
+        let game = &self.game;
+        let mut lines = Vec::new();
+
+        for is_white in [true, false] {
+            let mut line = vec![Span::raw(format!("{:<7}", team_name(is_white)))];
+
+            for (kind, c) in pocket::KINDS.iter().enumerate() {
+                let count = game.pockets[side(is_white)][kind];
+                if count == 0 {
+                    continue;
+                }
+
+                let style = match is_white == game.turn_white && self.dropping == Some(kind) {
+                    true => Style::default().fg(Color::Rgb(220, 139, 0)).add_modifier(Modifier::BOLD),
+                    false => Style::default()
+                };
+                line.push(Span::styled(format!("{}{} ", c, count), style));
+            }
+            lines.push(Spans::from(line));
+        }
+
+        let hint = match game.variant.passes_captures() {
+            true => "pnbrq drop, tab swaps",
+            false => "p/n/b/r/q to drop"
+        };
+        lines.push(Spans::from(Span::styled(hint, Style::default().fg(Color::Rgb(120, 120, 120)))));
+
+        Paragraph::new(lines)
+            .block(Block::default().title("Pockets").borders(Borders::ALL))
+    }
+}
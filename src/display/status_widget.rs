@@ -0,0 +1,123 @@
+
+
+
+use super::events::UserState;
+use crate::{engine::see::hanging, fen::square_name, game::{side, team_name}, uci};
+use ratatui::{
+    layout::Rect,
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph, Wrap},
+    style::{Style, Color, Modifier}
+};
+
+impl UserState {
+    // sits right underneath the board.
+    pub fn get_status_rect(&self) -> Rect {
+        let board = self.get_rect();
+        Rect { x: board.x, y: board.bottom() + 1, width: board.width, height: 6 }
+    }
+
+    // the pieces the player about to move could lose, like "Hanging: Qd1 Nf3".
+    // it's left out where it would tell them about pieces they can't see, or where captures blow everything up anyway.
+    fn hanging_warning(&self) -> Option<String> {
+        let game = &self.game;
+        let theirs = self.computer[side(game.turn_white)] || self.peer.as_ref().is_some_and(|peer| peer.is_white == game.turn_white);
+        if !self.settings.warn_hanging || theirs || game.variant.hides() || game.variant.explodes() {
+            return None;
+        }
+
+        let pieces: Vec<String> = hanging(game, game.turn_white).into_iter().map(|loc| {
+            let piece = game.board.squares[loc[1] as usize - 1][loc[0] as usize - 1];
+            format!("{}{}", char::from(piece), square_name(game.board.height, loc))
+        }).collect();
+        match pieces.is_empty() {
+            true => None,
+            false => Some(format!("Hanging: {}", pieces.join(" ")))
+        }
+    }
+
+    // what the external engine makes of the game, like "Engine d18 +0.35 e2e4 e7e5 g1f3", with the score from White's side.
+    fn analysis_line(&self) -> Option<String> {
+        let info = match &self.analysis {
+            Some(analysis) => match &analysis.info {
+                Some(info) => info,
+                None => {return Some("Engine: thinking...".to_string());}
+            },
+            None => {return None;}
+        };
+        let pv = uci::line(&self.game, &info.pv);
+        Some(format!("Engine d{} {} {}", info.depth, info.score.for_white(self.game.turn_white), pv[..pv.len().min(6)].join(" ")))
+    }
+
+    pub fn status_bar(&self) -> Paragraph<'_> {
+
+        let game = &self.game;
+        let mut lines = Vec::new();
+
+        match &game.outcome {
+            None if self.hidden => {
+                lines.push(Spans::from(Span::styled(format!("Pass the game over to {}.", team_name(game.turn_white)),
+                    Style::default().add_modifier(Modifier::BOLD))));
+                lines.push(Spans::from("Press any key when they're ready."));
+            },
+            Some(outcome) => {
+                lines.push(Spans::from(Span::styled(outcome.to_string(),
+                    Style::default().fg(Color::Rgb(13, 255, 0)).add_modifier(Modifier::BOLD))));
+                lines.push(Spans::from("Press any key to return to the menu."));
+            },
+            None => {
+                let mut turn = vec![Span::raw(format!("{} to move", team_name(game.turn_white)))];
+                if game.variant.in_check(&game.board, game.turn_white) {
+                    turn.push(Span::styled(" - Check!",
+                        Style::default().fg(Color::Rgb(255, 70, 70)).add_modifier(Modifier::BOLD)));
+                }
+                lines.push(Spans::from(turn));
+
+                if let Some(clock) = &game.clock {
+                    // the side whose time is running is shown in bold.
+                    let time = |is_white: bool| {
+                        let style = match is_white == game.turn_white {
+                            true => Style::default().add_modifier(Modifier::BOLD),
+                            false => Style::default().fg(Color::Rgb(120, 120, 120))
+                        };
+                        Span::styled(format!("{} {}", team_name(is_white), clock.format(is_white)), style)
+                    };
+                    lines.push(Spans::from(vec![time(true), Span::raw("  "), time(false)]));
+                }
+
+                if game.variant.counts_checks() {
+                    lines.push(Spans::from(format!("Checks: {} {}  {} {}",
+                        team_name(true), game.checks_given(true), team_name(false), game.checks_given(false))));
+                }
+
+                if !self.umpire.is_empty() {
+                    let said: Vec<String> = self.umpire.iter().map(|announcement| announcement.to_string()).collect();
+                    lines.push(Spans::from(format!("Umpire: {}", said.join(" "))));
+                }
+
+                if let Some(warning) = self.hanging_warning() {
+                    lines.push(Spans::from(Span::styled(warning, Style::default().fg(Color::Rgb(255, 170, 60)))));
+                }
+
+                if let Some(analysis) = self.analysis_line() {
+                    lines.push(Spans::from(Span::styled(analysis, Style::default().fg(Color::Rgb(120, 170, 255)))));
+                }
+
+                if let Some(message) = &self.message {
+                    lines.push(Spans::from(Span::styled(message.as_str(),
+                        Style::default().fg(Color::Rgb(220, 139, 0)))));
+                }
+            }
+        }
+
+        // the two Bughouse boards each have their own.
+        let title = match game.variant.passes_captures() {
+            true => format!("Board {}", self.board_number + 1),
+            false => "Status".to_string()
+        };
+
+        Paragraph::new(lines)
+            .block(Block::default().title(title).borders(Borders::ALL))
+            .wrap(Wrap { trim: true })
+    }
+}
@@ -0,0 +1,52 @@
+use std::time::{Duration, Instant};
+use crate::fen::from_fen;
+use super::{search::{search, Options, Search}, table::{Stats, Table}, time::Limits};
+
+
+// a fixed set of positions searched to a fixed depth, so the node counts can be compared
+// with parts of the search turned off, or before and after a change.
+
+pub const POSITIONS: [&str; 8] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4",
+    "r2q1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N1PN2/PP3PPP/R2QKB1R w KQ - 0 9",
+    "2r3k1/5ppp/p3p3/1p1nP3/3P4/P4N2/1P3PPP/2R3K1 w - - 0 25",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "6k1/5p2/6p1/8/7p/8/6PP/6K1 b - - 0 1",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10"
+];
+
+pub struct Bench {
+    pub searches: Vec<Search>,
+    pub nodes: u64,
+    pub time: Duration,
+    // the tables' statistics, added up over every position.
+    pub stats: Stats
+}
+
+impl Bench {
+    pub fn nodes_per_second(&self) -> u64 {
+        (self.nodes as f64 / self.time.as_secs_f64().max(0.001)) as u64
+    }
+}
+
+// every position gets a table of its own, so the order they're searched in doesn't change anything.
+pub fn bench(depth: u32, mb: usize, options: Options) -> Bench {
+    let start = Instant::now();
+    let mut searches = Vec::new();
+    let mut stats = Stats::default();
+
+    for fen in POSITIONS {
+        let game = from_fen(fen).expect("the bench positions are all valid");
+        let mut table = Table::new(mb);
+        searches.push(search(&game, &Limits::depth(depth), &mut table, options));
+
+        stats.probes += table.stats.probes;
+        stats.hits += table.stats.hits;
+        stats.stores += table.stats.stores;
+        stats.replaced += table.stats.replaced;
+    }
+
+    Bench { nodes: searches.iter().map(|search| search.nodes).sum(), searches, time: start.elapsed(), stats }
+}
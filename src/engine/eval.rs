@@ -0,0 +1,379 @@
+use std::{fmt, ops::{Add, AddAssign, Neg, Sub}};
+use crate::{
+    board::{Board, Loc, read_board},
+    check::get_king,
+    game::Game,
+    piece::move_list,
+    types::{Direction, Movement::*, Space, Space::*}
+};
+
+
+// scores positions in centipawns. every term is worked out twice, once for the middlegame and once for the endgame,
+// and the two are blended by how much is left on the board, so kings hide early and come out late.
+// the terms are kept from white's point of view until the very end.
+
+// a middlegame and an endgame score.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Score {
+    pub mg: i32,
+    pub eg: i32
+}
+
+const fn s(mg: i32, eg: i32) -> Score {
+    Score { mg, eg }
+}
+
+impl Add for Score {
+    type Output = Score;
+    fn add(self, other: Score) -> Score {
+        s(self.mg + other.mg, self.eg + other.eg)
+    }
+}
+
+impl AddAssign for Score {
+    fn add_assign(&mut self, other: Score) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Score {
+    type Output = Score;
+    fn sub(self, other: Score) -> Score {
+        s(self.mg - other.mg, self.eg - other.eg)
+    }
+}
+
+impl Neg for Score {
+    type Output = Score;
+    fn neg(self) -> Score {
+        s(-self.mg, -self.eg)
+    }
+}
+
+impl Score {
+    fn times(self, n: i32) -> Score {
+        s(self.mg * n, self.eg * n)
+    }
+}
+
+// how much of the game is left, from PHASE for all the pieces down to 0 for bare kings and pawns.
+pub const PHASE: i32 = 24;
+
+fn phase_weight(piece: Space) -> i32 {
+    match piece {
+        Knight(_) | Bishop(_) => 1,
+        Rook(_, _) => 2,
+        Archbishop(_) => 3,
+        Queen(_) | Chancellor(_) => 4,
+        _ => 0
+    }
+}
+
+pub fn value(piece: Space) -> Score {
+    match piece {
+        Pawn(_, _) => s(100, 120),
+        Knight(_) => s(320, 300),
+        Bishop(_) => s(330, 320),
+        Rook(_, _) => s(500, 540),
+        Queen(_) => s(950, 1000),
+        Archbishop(_) => s(850, 850),
+        Chancellor(_) => s(900, 930),
+        // nothing is known about made up pieces, so they're guessed at about a minor piece.
+        Fairy(_, _, _) => s(300, 300),
+        King(_, _) | Open => s(0, 0)
+    }
+}
+
+// piece-square tables, from white's side with the eighth rank first, the way a board is printed.
+const PAWN: [i32; 64] = [
+      0,   0,   0,   0,   0,   0,   0,   0,
+     50,  50,  50,  50,  50,  50,  50,  50,
+     10,  10,  20,  30,  30,  20,  10,  10,
+      5,   5,  10,  25,  25,  10,   5,   5,
+      0,   0,   0,  20,  20,   0,   0,   0,
+      5,  -5, -10,   0,   0, -10,  -5,   5,
+      5,  10,  10, -20, -20,  10,  10,   5,
+      0,   0,   0,   0,   0,   0,   0,   0
+];
+
+// in the endgame a pawn is worth more the closer it is to promoting, wherever it is.
+const PAWN_END: [i32; 64] = [
+      0,   0,   0,   0,   0,   0,   0,   0,
+     40,  40,  40,  40,  40,  40,  40,  40,
+     25,  25,  25,  25,  25,  25,  25,  25,
+     15,  15,  15,  15,  15,  15,  15,  15,
+      8,   8,   8,   8,   8,   8,   8,   8,
+      3,   3,   3,   3,   3,   3,   3,   3,
+      0,   0,   0,   0,   0,   0,   0,   0,
+      0,   0,   0,   0,   0,   0,   0,   0
+];
+
+const KNIGHT: [i32; 64] = [
+    -50, -40, -30, -30, -30, -30, -40, -50,
+    -40, -20,   0,   0,   0,   0, -20, -40,
+    -30,   0,  10,  15,  15,  10,   0, -30,
+    -30,   5,  15,  20,  20,  15,   5, -30,
+    -30,   0,  15,  20,  20,  15,   0, -30,
+    -30,   5,  10,  15,  15,  10,   5, -30,
+    -40, -20,   0,   5,   5,   0, -20, -40,
+    -50, -40, -30, -30, -30, -30, -40, -50
+];
+
+const BISHOP: [i32; 64] = [
+    -20, -10, -10, -10, -10, -10, -10, -20,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -10,   0,   5,  10,  10,   5,   0, -10,
+    -10,   5,   5,  10,  10,   5,   5, -10,
+    -10,   0,  10,  10,  10,  10,   0, -10,
+    -10,  10,  10,  10,  10,  10,  10, -10,
+    -10,   5,   0,   0,   0,   0,   5, -10,
+    -20, -10, -10, -10, -10, -10, -10, -20
+];
+
+const ROOK: [i32; 64] = [
+      0,   0,   0,   0,   0,   0,   0,   0,
+      5,  10,  10,  10,  10,  10,  10,   5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+      0,   0,   0,   5,   5,   0,   0,   0
+];
+
+const QUEEN: [i32; 64] = [
+    -20, -10, -10,  -5,  -5, -10, -10, -20,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -10,   0,   5,   5,   5,   5,   0, -10,
+     -5,   0,   5,   5,   5,   5,   0,  -5,
+      0,   0,   5,   5,   5,   5,   0,  -5,
+    -10,   5,   5,   5,   5,   5,   0, -10,
+    -10,   0,   5,   0,   0,   0,   0, -10,
+    -20, -10, -10,  -5,  -5, -10, -10, -20
+];
+
+const KING: [i32; 64] = [
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -20, -30, -30, -40, -40, -30, -30, -20,
+    -10, -20, -20, -20, -20, -20, -20, -10,
+     20,  20,   0,   0,   0,   0,  20,  20,
+     20,  30,  10,   0,   0,  10,  30,  20
+];
+
+const KING_END: [i32; 64] = [
+    -50, -40, -30, -20, -20, -30, -40, -50,
+    -30, -20, -10,   0,   0, -10, -20, -30,
+    -30, -10,  20,  30,  30,  20, -10, -30,
+    -30, -10,  30,  40,  40,  30, -10, -30,
+    -30, -10,  30,  40,  40,  30, -10, -30,
+    -30, -10,  20,  30,  30,  20, -10, -30,
+    -30, -30,   0,   0,   0,   0, -30, -30,
+    -50, -30, -30, -30, -30, -30, -30, -50
+];
+
+// where on its table a piece stands. black reads the tables upside down,
+// and boards that aren't 8x8 are squashed or stretched to fit.
+fn square(board: &Board, loc: Loc, is_white: bool) -> usize {
+    let row = match is_white {
+        true => loc[1] - 1,
+        false => board.height - loc[1]
+    };
+    let (row, column) = (row * 8 / board.height, (loc[0] - 1) * 8 / board.width);
+    (row * 8 + column) as usize
+}
+
+fn placement(board: &Board, loc: Loc, piece: Space) -> Score {
+    let i = square(board, loc, piece.is_white());
+    match piece {
+        Pawn(_, _) => s(PAWN[i], PAWN_END[i]),
+        Knight(_) => s(KNIGHT[i], KNIGHT[i]),
+        Bishop(_) => s(BISHOP[i], BISHOP[i]),
+        Rook(_, _) => s(ROOK[i], ROOK[i]),
+        Queen(_) | Archbishop(_) | Chancellor(_) => s(QUEEN[i], QUEEN[i]),
+        King(_, _) => s(KING[i], KING_END[i]),
+        Fairy(_, _, _) | Open => s(0, 0)
+    }
+}
+
+// what each square a piece could move to is worth.
+fn mobility_weight(piece: Space) -> Score {
+    match piece {
+        Knight(_) => s(4, 4),
+        Bishop(_) => s(5, 5),
+        Rook(_, _) => s(2, 4),
+        Queen(_) => s(1, 2),
+        Archbishop(_) => s(3, 3),
+        Chancellor(_) | Fairy(_, _, _) => s(2, 3),
+        Pawn(_, _) | King(_, _) | Open => s(0, 0)
+    }
+}
+
+const DOUBLED: Score = s(-10, -20);
+const ISOLATED: Score = s(-15, -10);
+const BISHOP_PAIR: Score = s(30, 50);
+// for each pawn in front of the king, on the next rank and the one after.
+const SHIELD: [i32; 2] = [12, 6];
+// for each move an enemy piece has onto a square next to the king.
+const KING_ATTACK: i32 = -8;
+
+// passed pawns, by how far up the board they've got, from nothing at the start to most of a piece right before promoting.
+fn passed(advanced: i32) -> Score {
+    s(5 * advanced, 3 * advanced * advanced)
+}
+
+// why a position got the score it did. every term is from white's point of view.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Breakdown {
+    pub phase: i32,
+    pub material: Score,
+    pub placement: Score,
+    pub mobility: Score,
+    pub pawns: Score,
+    pub king_safety: Score,
+    pub bishop_pair: Score
+}
+
+impl Breakdown {
+    pub fn terms(&self) -> [(&'static str, Score); 6] {
+        [
+            ("Material", self.material),
+            ("Placement", self.placement),
+            ("Mobility", self.mobility),
+            ("Pawns", self.pawns),
+            ("King safety", self.king_safety),
+            ("Bishop pair", self.bishop_pair)
+        ]
+    }
+
+    // blends a middlegame and endgame score by how far along the game is.
+    pub fn taper(&self, score: Score) -> i32 {
+        (score.mg * self.phase + score.eg * (PHASE - self.phase)) / PHASE
+    }
+
+    // the whole score, for white.
+    pub fn total(&self) -> i32 {
+        self.taper(self.terms().iter().fold(Score::default(), |sum, (_, score)| sum + *score))
+    }
+}
+
+impl fmt::Display for Breakdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<12}{:>7}{:>7}{:>7}", "", "mg", "eg", "total")?;
+        for (name, score) in self.terms() {
+            writeln!(f, "{:<12}{:>7}{:>7}{:>7}", name, score.mg, score.eg, self.taper(score))?;
+        }
+        write!(f, "{:<12}{:>21}  (phase {}/{})", "Total", self.total(), self.phase, PHASE)
+    }
+}
+
+// the pawn terms for one side: doubled and isolated pawns, and passed ones.
+fn pawn_structure(board: &Board, is_white: bool) -> Score {
+    let pawns_on = |x: isize, w: bool| (1..=board.height).filter(move |y| matches!(read_board(board, [x, *y]), Some(Pawn(pw, _)) if pw == w));
+
+    let mut score = Score::default();
+    for x in 1..=board.width {
+        let count = pawns_on(x, is_white).count() as i32;
+        if count == 0 {
+            continue;
+        }
+        if count > 1 {
+            score += DOUBLED.times(count - 1);
+        }
+        if pawns_on(x - 1, is_white).next().is_none() && pawns_on(x + 1, is_white).next().is_none() {
+            score += ISOLATED.times(count);
+        }
+
+        // nothing of the other side's on this file or either side of it stands in the way.
+        for y in pawns_on(x, is_white) {
+            let ahead = |other: isize| if is_white {other < y} else {other > y};
+            let blocked = (x - 1..=x + 1).any(|file| pawns_on(file, !is_white).any(ahead));
+            if !blocked {
+                // counted as if on an 8x8 board, from nothing on the second rank.
+                let advanced = if is_white {board.height - y} else {y - 1} as i32 - 1;
+                score += passed(advanced.max(0) * 8 / board.height as i32);
+            }
+        }
+    }
+    score
+}
+
+// pawns sheltering the king count for it, and enemy pieces bearing down on it count against.
+// only matters while there's enough on the board to attack with, which the taper sees to.
+fn king_shelter(board: &Board, is_white: bool) -> i32 {
+    let kingc = match get_king(board, is_white) {
+        Some(kingc) => kingc,
+        None => {return 0;}
+    };
+    let forward = if is_white {Direction::South} else {Direction::North};
+    let mut shelter = 0;
+    for (n, bonus) in SHIELD.iter().enumerate() {
+        let row = forward.translate(kingc, n as isize + 1);
+        for dx in -1..=1 {
+            if read_board(board, [row[0] + dx, row[1]]).is_some_and(|space| matches!(space, Pawn(w, _) if w == is_white)) {
+                shelter += bonus;
+            }
+        }
+    }
+    shelter
+}
+
+fn near_king(board: &Board, kingc: Option<Loc>, loc: Loc) -> bool {
+    kingc.is_some_and(|kingc| loc != kingc && (loc[0] - kingc[0]).abs() <= 1 && (loc[1] - kingc[1]).abs() <= 1 && board.contains(loc))
+}
+
+pub fn breakdown(board: &Board) -> Breakdown {
+
+    let mut terms = Breakdown::default();
+    let kings = [get_king(board, true), get_king(board, false)];
+    let mut bishops = [0, 0];
+    let mut attacks = [0, 0];
+
+    for y in 1..=board.height {
+        for x in 1..=board.width {
+            let piece = match read_board(board, [x, y]) {
+                Some(Open) | None => {continue;},
+                Some(piece) => piece
+            };
+            let is_white = piece.is_white();
+            let sign = |score: Score| if is_white {score} else {-score};
+
+            terms.phase += phase_weight(piece);
+            terms.material += sign(value(piece));
+            terms.placement += sign(placement(board, [x, y], piece));
+
+            if let Bishop(_) = piece {
+                bishops[if is_white {0} else {1}] += 1;
+            }
+
+            let weight = mobility_weight(piece);
+            if weight != Score::default() {
+                let moves = move_list(board, [x, y], piece);
+                let reach = moves.iter().filter(|movement| matches!(movement.relation, Empty | Enemy)).count() as i32;
+                terms.mobility += sign(weight.times(reach));
+
+                let enemy_king = kings[if is_white {1} else {0}];
+                attacks[if is_white {0} else {1}] += moves.iter()
+                    .filter(|movement| movement.relation != Blocked && near_king(board, enemy_king, movement.to)).count() as i32;
+            }
+        }
+    }
+
+    terms.phase = terms.phase.min(PHASE);
+    terms.pawns = pawn_structure(board, true) - pawn_structure(board, false);
+    terms.king_safety = s(king_shelter(board, true) - king_shelter(board, false) + KING_ATTACK * (attacks[1] - attacks[0]), 0);
+    terms.bishop_pair = match (bishops[0] >= 2, bishops[1] >= 2) {
+        (true, false) => BISHOP_PAIR,
+        (false, true) => -BISHOP_PAIR,
+        _ => Score::default()
+    };
+    terms
+}
+
+// the score from the point of view of the side to move.
+pub fn evaluate(game: &Game) -> i32 {
+    let total = breakdown(&game.board).total();
+    if game.turn_white {total} else {-total}
+}
@@ -0,0 +1,87 @@
+pub mod bench;
+pub mod eval;
+pub mod order;
+pub mod search;
+pub mod see;
+pub mod table;
+pub mod time;
+
+use crate::{
+    board::{Loc, read_board},
+    fen::square_name,
+    game::{Game, side},
+    pocket::{self, drop_squares},
+    types::{IllegalMove, Movement::*, Promotion, Space::*}
+};
+
+
+pub use search::search;
+
+// how the computer picks its moves: every legal move is tried,
+// and the search module looks ahead through them for the best one.
+
+// a move or drop, as the computer hands it back.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Play {
+    Move(Loc, Loc, Promotion),
+    Drop(usize, Loc)
+}
+
+impl Play {
+    pub fn play(self, game: &mut Game) -> Result<(), IllegalMove> {
+        match self {
+            Play::Move(fromc, toc, promotion) => game.play_promoting(fromc, toc, promotion),
+            Play::Drop(kind, toc) => game.drop(kind, toc)
+        }
+    }
+
+    // the way engines write moves down: "e2e4", "e7e8q" for a promotion, and "N@f3" for a drop.
+    // a pawn that could promote but doesn't gets no letter.
+    pub fn name(self, game: &Game) -> String {
+        let board = &game.board;
+        match self {
+            Play::Move(fromc, toc, promotion) => {
+                let mut name = square_name(board.height, fromc) + &square_name(board.height, toc);
+                if promotion != Promotion::Pawn && !game.variant.promotions_at(board, fromc, toc).is_empty() {
+                    name.push(promotion.letter().to_ascii_lowercase());
+                }
+                name
+            },
+            Play::Drop(kind, toc) => format!("{}@{}", pocket::KINDS[kind], square_name(board.height, toc))
+        }
+    }
+}
+
+// every move and drop the side to move could make, with a move for each piece a pawn could become.
+pub fn plays(game: &Game) -> Vec<Play> {
+    let board = &game.board;
+    let mut plays = Vec::new();
+
+    for y in 1..=board.height {
+        for x in 1..=board.width {
+            match read_board(board, [x, y]) {
+                Some(piece) if piece != Open && piece.is_white() == game.turn_white => (),
+                _ => {continue;}
+            }
+
+            for movement in game.variant.moves(board, [x, y]) {
+                if matches!(movement.relation, Check | Blocked) {
+                    continue;
+                }
+                let promotions = game.variant.promotions_at(board, [x, y], movement.to);
+                match promotions.is_empty() {
+                    false => plays.extend(promotions.into_iter().map(|promotion| Play::Move([x, y], movement.to, promotion))),
+                    true => plays.push(Play::Move([x, y], movement.to, game.variant.promotions()[0]))
+                }
+            }
+        }
+    }
+
+    if game.variant.has_pockets() {
+        let pocket = &game.pockets[side(game.turn_white)];
+        for kind in 0..pocket::KINDS.len() {
+            plays.extend(drop_squares(board, pocket, kind, game.turn_white).into_iter().map(|toc| Play::Drop(kind, toc)));
+        }
+    }
+    plays
+}
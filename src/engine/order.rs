@@ -0,0 +1,143 @@
+use crate::{
+    board::{Loc, MAX_SIZE, read_board},
+    game::Game,
+    types::{PawnState, Space, Space::*},
+    variant::promotes
+};
+use super::{Play, search::Options, see::{see, worth}};
+
+
+// the order moves are tried in. alpha-beta cuts off the most when the best move comes first,
+// so the likeliest ones go to the front: the table's move, then captures of big pieces by small ones,
+// then quiet moves that were good elsewhere in the search.
+
+// the piece a move takes, counting a pawn taken en passant.
+pub fn captured(game: &Game, play: Play) -> Option<Space> {
+    let (fromc, toc) = match play {
+        Play::Move(fromc, toc, _) => (fromc, toc),
+        Play::Drop(_, _) => {return None;}
+    };
+    match (read_board(&game.board, fromc), read_board(&game.board, toc)) {
+        (Some(from), Some(to)) if to != Open && to.is_white() != from.is_white() => Some(to),
+        (Some(Pawn(w, _)), Some(Open)) if fromc[0] != toc[0] => Some(Pawn(!w, PawnState::Moved)),
+        _ => None
+    }
+}
+
+// captures and promotions, the moves that change what's on the board.
+pub fn tactical(game: &Game, play: Play) -> bool {
+    match play {
+        Play::Move(fromc, toc, _) => captured(game, play).is_some() || promotes(&game.board, fromc, toc),
+        Play::Drop(_, _) => false
+    }
+}
+
+// a capture that looks like it loses material, which only needs working out when something is taking a smaller piece.
+pub fn losing(game: &Game, play: Play) -> bool {
+    match (play, captured(game, play)) {
+        (Play::Move(fromc, _, _), Some(victim)) => {
+            read_board(&game.board, fromc).is_some_and(|from| worth(from) > worth(victim)) && see(game, play) < 0
+        },
+        _ => false
+    }
+}
+
+const SQUARES: usize = MAX_SIZE * MAX_SIZE;
+
+fn index(loc: Loc) -> usize {
+    (loc[1] - 1) as usize * MAX_SIZE + (loc[0] - 1) as usize
+}
+
+// what the search has learned about quiet moves so far.
+pub struct Order {
+    // a couple of quiet moves that caused a cut off at each depth into the search,
+    // which are often just as good in the positions next to it.
+    killers: Vec<[Option<Play>; 2]>,
+    // how often each move from one square to another has caused a cut off anywhere, weighted towards deeper ones.
+    history: Box<[[i32; SQUARES]; SQUARES]>
+}
+
+impl Order {
+
+    pub fn new() -> Order {
+        Order { killers: Vec::new(), history: Box::new([[0; SQUARES]; SQUARES]) }
+    }
+
+    pub fn is_killer(&self, ply: u32, play: Play) -> bool {
+        self.killers.get(ply as usize).is_some_and(|killers| killers.contains(&Some(play)))
+    }
+
+    // a quiet move that was too good for the opponent to allow.
+    pub fn cut_off(&mut self, ply: u32, play: Play, depth: u32) {
+        let ply = ply as usize;
+        if self.killers.len() <= ply {
+            self.killers.resize(ply + 1, [None, None]);
+        }
+        if self.killers[ply][0] != Some(play) {
+            self.killers[ply] = [Some(play), self.killers[ply][0]];
+        }
+
+        if let Play::Move(fromc, toc, _) = play {
+            let score = &mut self.history[index(fromc)][index(toc)];
+            *score += (depth * depth) as i32;
+            // keeps recent cut offs counting for more than old ones.
+            if *score > 1 << 20 {
+                self.history.iter_mut().flatten().for_each(|score| *score /= 2);
+            }
+        }
+    }
+
+    fn score(&self, game: &Game, play: Play, ply: u32, options: Options) -> i32 {
+        let victim = captured(game, play);
+        let attacker = match play {
+            Play::Move(fromc, _, _) => read_board(&game.board, fromc).unwrap_or(Open),
+            Play::Drop(_, _) => Open
+        };
+
+        if let Some(victim) = victim {
+            if options.see && worth(attacker) > worth(victim) {
+                let exchange = see(game, play);
+                if exchange < 0 {
+                    return -(1 << 20) + exchange;
+                }
+            }
+            if options.mvv_lva {
+                return (1 << 29) + worth(victim) * 16 - worth(attacker) / 16;
+            }
+            if options.see {
+                return (1 << 29) + see(game, play);
+            }
+        }
+
+        // a promotion is as good as taking what it turns into.
+        if let Play::Move(fromc, toc, promotion) = play {
+            if options.mvv_lva && promotes(&game.board, fromc, toc) {
+                return (1 << 29) + worth(promotion.piece(game.turn_white)) * 16;
+            }
+        }
+
+        if options.killers {
+            if let Some(killers) = self.killers.get(ply as usize) {
+                if killers[0] == Some(play) {
+                    return 1 << 28;
+                }
+                if killers[1] == Some(play) {
+                    return (1 << 28) - 1;
+                }
+            }
+        }
+
+        match play {
+            Play::Move(fromc, toc, _) if options.history => self.history[index(fromc)][index(toc)],
+            _ => 0
+        }
+    }
+
+    // best first, with the table's move in front of everything. moves that score the same keep the order they came in.
+    pub fn sort(&self, game: &Game, plays: &mut [Play], hash_play: Option<Play>, ply: u32, options: Options) {
+        plays.sort_by_cached_key(|play| match Some(*play) == hash_play {
+            true => i32::MIN,
+            false => -self.score(game, *play, ply, options)
+        });
+    }
+}
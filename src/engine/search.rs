@@ -0,0 +1,431 @@
+use std::time::{Duration, Instant};
+use crate::{
+    game::{Game, Outcome},
+    types::{PawnState, Space::*}
+};
+use super::{Play, eval::evaluate, order::{self, Order}, plays, table::{self, Bound, Table}, time::{Limits, Stop, TimeManager}};
+
+
+// negamax with alpha-beta pruning: every score is from the point of view of the side to move,
+// so what's good for one side is the same number negated for the other.
+// it searches one move deep, then two, and so on, trying the best move so far first each time.
+// positions already searched deep enough are looked up in the table instead of searched again.
+// at the end of each line it keeps going through the captures, so it doesn't stop in the middle of a trade.
+// it can be stopped at any point, and then plays the best move it had got to.
+
+// more than any position could be worth, and what getting mated is worth, less the moves it takes.
+pub const INFINITY: i32 = 1_000_000;
+pub const MATE: i32 = 100_000;
+// anything past this is a mate found some number of moves away.
+pub const MATE_BOUND: i32 = MATE - 1000;
+// how far into a line the search will ever go, whatever extends it.
+pub const MAX_PLY: u32 = 64;
+
+// the parts of the search that can be turned off, to see what each one is worth.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Options {
+    // follows captures and promotions past the end of each line.
+    pub quiescence: bool,
+    // tries taking the most valuable piece with the least valuable one first.
+    pub mvv_lva: bool,
+    // tries captures that lose material after everything else, and leaves them out of the quiescence search.
+    pub see: bool,
+    pub killers: bool,
+    pub history: bool,
+    // lets the opponent move twice, and if they still can't do better than they have, doesn't bother looking.
+    pub null_move: bool,
+    // looks less deeply at quiet moves that come late in the order.
+    pub reductions: bool,
+    // looks a move deeper when in check.
+    pub check_extensions: bool
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            quiescence: true,
+            mvv_lva: true,
+            see: true,
+            killers: true,
+            history: true,
+            null_move: true,
+            reductions: true,
+            check_extensions: true
+        }
+    }
+}
+
+impl Options {
+
+    pub const NAMES: [&'static str; 8] = ["quiescence", "mvv-lva", "see", "killers", "history", "null-move", "reductions", "check-extensions"];
+
+    // turns one on or off by its name, giving back whether there's one called that.
+    pub fn set(&mut self, name: &str, on: bool) -> bool {
+        let option = match name {
+            "quiescence" => &mut self.quiescence,
+            "mvv-lva" => &mut self.mvv_lva,
+            "see" => &mut self.see,
+            "killers" => &mut self.killers,
+            "history" => &mut self.history,
+            "null-move" => &mut self.null_move,
+            "reductions" => &mut self.reductions,
+            "check-extensions" => &mut self.check_extensions,
+            _ => {return false;}
+        };
+        *option = on;
+        true
+    }
+}
+
+// how the search went.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Search {
+    pub play: Option<Play>,
+    pub score: i32,
+    // the deepest search that finished.
+    pub depth: u32,
+    // the furthest into a line it went, counting captures and extensions.
+    pub seldepth: u32,
+    pub nodes: u64,
+    pub time: Duration,
+    // the line it expects to be played, starting with its own move.
+    pub pv: Vec<Play>
+}
+
+impl Search {
+    pub fn nodes_per_second(&self) -> u64 {
+        (self.nodes as f64 / self.time.as_secs_f64().max(0.001)) as u64
+    }
+}
+
+// a finished game is won, lost or drawn for the side to move, and sooner is better when winning.
+fn outcome_score(outcome: Outcome, turn_white: bool, ply: u32) -> i32 {
+    match outcome.winner() {
+        Some(w) if w == turn_white => MATE - ply as i32,
+        Some(_) => -(MATE - ply as i32),
+        None => 0
+    }
+}
+
+// what the search carries around with it.
+struct Context<'a> {
+    table: &'a mut Table,
+    options: Options,
+    order: Order,
+    nodes: u64,
+    seldepth: u32,
+    time: TimeManager,
+    node_limit: Option<u64>,
+    stop: Stop,
+    // once set, every score is meaningless and the search is on its way out.
+    stopped: bool
+}
+
+impl Context<'_> {
+    // counts a node, and says whether it's time to give up.
+    // the clock is only looked at every so often, since that takes a while.
+    fn visit(&mut self, ply: u32) -> bool {
+        self.nodes += 1;
+        self.seldepth = self.seldepth.max(ply);
+        if self.nodes.is_multiple_of(1024) {
+            self.stopped |= self.time.out_of_time();
+        }
+        self.stopped |= self.stop.is_stopped() || self.node_limit.is_some_and(|limit| self.nodes >= limit);
+        self.stopped
+    }
+}
+
+// the side to move does nothing, for null move pruning. a pawn that could have been taken en passant can't be any more.
+fn pass(game: &Game) -> Game {
+    let mut next = game.clone();
+    next.turn_white = !next.turn_white;
+    for space in next.board.squares.iter_mut().flatten() {
+        if let Pawn(w, PawnState::PrevSkipped) = *space {
+            *space = Pawn(w, PawnState::Moved);
+        }
+    }
+    next
+}
+
+// with only pawns left, passing can be the best move there is, so null move pruning would be fooled.
+fn has_pieces(game: &Game) -> bool {
+    game.board.spaces().any(|piece| {
+        piece != Open && piece.is_white() == game.turn_white && !matches!(piece, Pawn(_, _) | King(_, _))
+    })
+}
+
+// only captures and promotions, until there are none left that are worth it.
+// the side to move can always decline them and keep what it has instead.
+fn quiesce(cx: &mut Context, game: &Game, mut alpha: i32, beta: i32, ply: u32) -> i32 {
+    if cx.visit(ply) {
+        return 0;
+    }
+
+    if let Some(outcome) = game.outcome {
+        return outcome_score(outcome, game.turn_white, ply);
+    }
+    let stand = evaluate(game);
+    if stand >= beta || ply >= MAX_PLY {
+        return stand;
+    }
+    alpha = alpha.max(stand);
+
+    // promoting to anything but the first choice is left for the full search to think about.
+    let first = game.variant.promotions()[0];
+    let mut order: Vec<Play> = plays(game).into_iter()
+        .filter(|play| matches!(play, Play::Move(_, _, promotion) if *promotion == first) && order::tactical(game, *play))
+        .collect();
+    cx.order.sort(game, &mut order, None, ply, cx.options);
+
+    let mut best = stand;
+    for play in order {
+        if cx.options.see && order::losing(game, play) {
+            continue;
+        }
+        let mut next = game.clone();
+        if play.play(&mut next).is_err() {
+            continue;
+        }
+
+        let score = -quiesce(cx, &next, -beta, -alpha, ply + 1);
+        if cx.stopped {
+            return 0;
+        }
+        best = best.max(score);
+        alpha = alpha.max(score);
+        if alpha >= beta {
+            break;
+        }
+    }
+    best
+}
+
+fn negamax(cx: &mut Context, game: &Game, depth: u32, mut alpha: i32, beta: i32, ply: u32, null_ok: bool) -> i32 {
+
+    let in_check = game.outcome.is_none() && game.variant.in_check(&game.board, game.turn_white);
+    let depth = if in_check && cx.options.check_extensions {depth + 1} else {depth};
+
+    if depth == 0 || ply >= MAX_PLY {
+        if cx.options.quiescence {
+            return quiesce(cx, game, alpha, beta, ply);
+        }
+        if cx.visit(ply) {
+            return 0;
+        }
+        return match game.outcome {
+            Some(outcome) => outcome_score(outcome, game.turn_white, ply),
+            None => evaluate(game)
+        };
+    }
+
+    if cx.visit(ply) {
+        return 0;
+    }
+    if let Some(outcome) = game.outcome {
+        return outcome_score(outcome, game.turn_white, ply);
+    }
+
+    let hash = table::hash(game);
+    let entry = cx.table.probe(hash);
+    if let Some(entry) = entry.filter(|entry| entry.depth >= depth) {
+        let score = entry.score(ply);
+        match entry.bound {
+            Bound::Exact => {return score;},
+            Bound::Lower if score >= beta => {return score;},
+            Bound::Upper if score <= alpha => {return score;},
+            _ => ()
+        }
+    }
+
+    // if passing still leaves the opponent worse off than they can already make sure of, a real move will too.
+    if cx.options.null_move && null_ok && !in_check && depth >= 3 && beta.abs() < MATE_BOUND && has_pieces(game) {
+        let reduction = if depth > 6 {3} else {2};
+        let score = -negamax(cx, &pass(game), depth - 1 - reduction, -beta, -beta + 1, ply + 1, false);
+        if cx.stopped {
+            return 0;
+        }
+        if score >= beta {
+            return beta;
+        }
+    }
+
+    let mut order = plays(game);
+    cx.order.sort(game, &mut order, entry.and_then(|entry| entry.play), ply, cx.options);
+
+    let start = alpha;
+    let mut best = -INFINITY;
+    let mut best_play = None;
+    let mut searched = 0;
+    for play in order {
+        let tactical = order::tactical(game, play);
+        let killer = cx.order.is_killer(ply, play);
+        let mut next = game.clone();
+        if play.play(&mut next).is_err() {
+            continue;
+        }
+
+        // late quiet moves are probably no good, so they get a quick look first, and a proper one if they turn out to be.
+        let late = cx.options.reductions && depth >= 3 && searched >= 3 && !in_check && !tactical && !killer
+            && !next.variant.in_check(&next.board, next.turn_white);
+        let score = match late {
+            true => match -negamax(cx, &next, depth - 2, -alpha - 1, -alpha, ply + 1, true) {
+                score if score > alpha => -negamax(cx, &next, depth - 1, -beta, -alpha, ply + 1, true),
+                score => score
+            },
+            false => -negamax(cx, &next, depth - 1, -beta, -alpha, ply + 1, true)
+        };
+        searched += 1;
+        if cx.stopped {
+            return 0;
+        }
+
+        if score > best {
+            best = score;
+            best_play = Some(play);
+        }
+        alpha = alpha.max(score);
+        if alpha >= beta {
+            if !tactical {
+                cx.order.cut_off(ply, play, depth);
+            }
+            break;
+        }
+    }
+
+    // some variants can leave a side without a move and without the game being over, like a Bughouse
+    // player waiting on a piece from the other board. that's neither won nor lost yet.
+    if best == -INFINITY {
+        return evaluate(game);
+    }
+
+    let bound = match best {
+        b if b <= start => Bound::Upper,
+        b if b >= beta => Bound::Lower,
+        _ => Bound::Exact
+    };
+    cx.table.store(hash, depth, bound, best, ply, best_play);
+    best
+}
+
+// the line the search expects, after the given first move: the table's best move in each position along it,
+// for as long as there is one and it's legal.
+fn principal_variation(root: &Game, first: Play, table: &Table, depth: u32) -> Vec<Play> {
+    let mut pv = vec![first];
+    let mut game = root.clone();
+    if first.play(&mut game).is_err() {
+        return pv;
+    }
+    while pv.len() < depth.max(1) as usize && game.outcome.is_none() {
+        let play = match table.peek(table::hash(&game)).and_then(|entry| entry.play) {
+            Some(play) if plays(&game).contains(&play) => play,
+            _ => {break;}
+        };
+        if play.play(&mut game).is_err() {
+            break;
+        }
+        pv.push(play);
+    }
+    pv
+}
+
+// looks for the side to move's best play, as deep as the limits allow.
+// the table can be kept from one move to the next, since much of what it knows will still come up.
+pub fn search(game: &Game, limits: &Limits, table: &mut Table, options: Options) -> Search {
+    search_with(game, limits, table, options, |_, _| ())
+}
+
+// the same, telling 'report' how it's going each time it finishes looking a move deeper.
+pub fn search_with(game: &Game, limits: &Limits, table: &mut Table, options: Options, mut report: impl FnMut(&Search, &Table)) -> Search {
+    let start = Instant::now();
+
+    // the clock keeps running in real time, which would end games part way through the search.
+    let mut root = game.clone();
+    root.clock = None;
+
+    table.new_search();
+    let hash = table::hash(&root);
+    let hash_play = table.probe(hash).and_then(|entry| entry.play);
+
+    let mut cx = Context {
+        table,
+        options,
+        order: Order::new(),
+        nodes: 0,
+        seldepth: 0,
+        time: TimeManager::new(limits, root.turn_white),
+        node_limit: limits.nodes,
+        stop: limits.stop.clone(),
+        stopped: false
+    };
+    let mut order = plays(&root);
+    order.retain(|play| limits.moves.is_empty() || limits.moves.contains(play));
+    cx.order.sort(&root, &mut order, hash_play, 0, options);
+
+    // stopped before it's even looked, it still has something to play.
+    let mut result = Search { play: order.first().copied(), pv: order.first().copied().into_iter().collect(), ..Search::default() };
+
+    for depth in 1..=limits.depth.unwrap_or(MAX_PLY).clamp(1, MAX_PLY) {
+        let mut best: Option<(Play, i32)> = None;
+        let mut alpha = -INFINITY;
+
+        for play in &order {
+            let mut next = root.clone();
+            if play.play(&mut next).is_err() {
+                continue;
+            }
+
+            let score = -negamax(&mut cx, &next, depth - 1, -INFINITY, -alpha, 1, true);
+            if cx.stopped {
+                break;
+            }
+            if best.is_none_or(|(_, best)| score > best) {
+                best = Some((*play, score));
+                alpha = score;
+            }
+        }
+
+        let (play, score) = match best {
+            Some(best) => best,
+            None => {break;}
+        };
+
+        // part way through a depth, the last best move was looked at first, so another one that's been
+        // searched all the way and beat it is better still.
+        if cx.stopped {
+            if result.depth == 0 || result.play != Some(play) {
+                result = Search { play: Some(play), score, pv: vec![play], ..result };
+            }
+            break;
+        }
+
+        match result.play == Some(play) {
+            true => cx.time.stable(),
+            false => cx.time.unstable()
+        }
+        if result.depth > 0 && score < result.score - 50 {
+            cx.time.unstable();
+        }
+        cx.table.store(hash, depth, Bound::Exact, score, 0, Some(play));
+        result = Search {
+            play: Some(play),
+            score,
+            depth,
+            seldepth: cx.seldepth.max(depth),
+            nodes: cx.nodes,
+            time: start.elapsed(),
+            pv: principal_variation(&root, play, cx.table, depth)
+        };
+        report(&result, cx.table);
+
+        // the best move so far goes first next time, which lets the rest be cut off sooner.
+        if let Some(i) = order.iter().position(|other| *other == play) {
+            order[..=i].rotate_right(1);
+        }
+
+        // no point looking further once a forced win is found, or when there isn't time to.
+        if score.abs() >= MATE - depth as i32 || !cx.time.another() {
+            break;
+        }
+    }
+    Search { nodes: cx.nodes, time: start.elapsed(), ..result }
+}
@@ -0,0 +1,133 @@
+use crate::{
+    board::{Board, Loc, read_board, write_board},
+    check::attackers,
+    game::Game,
+    pocket,
+    types::{PawnState, Space, Space::*},
+    variant::last_rank
+};
+use super::{Play, eval::value};
+
+
+// static exchange evaluation: what a move wins or loses on the square it goes to, once both sides have
+// taken back there with their least valuable piece for as long as it pays them. it only looks at that one square,
+// and sees through to a rook behind a rook or a bishop behind a queen as the pieces in front get used up.
+// pins aren't looked at, so a pinned piece can join in when it really couldn't.
+
+// what a piece is worth for trading, the king being the last thing anyone wants to take with.
+pub fn worth(piece: Space) -> i32 {
+    match piece {
+        King(_, _) => 10_000,
+        piece => value(piece).mg
+    }
+}
+
+// a pawn that takes on the last rank goes on as the best piece there is.
+fn arrives(board: &Board, piece: Space, toc: Loc) -> Space {
+    match piece {
+        Pawn(w, _) if toc[1] == last_rank(board, w) => Queen(w),
+        piece => piece
+    }
+}
+
+// the cheapest piece the given team has attacking 'toc', not counting a king if the other team could take it there.
+fn least_valuable(board: &Board, toc: Loc, is_white: bool) -> Option<Loc> {
+    let fromc = attackers(board, toc, !is_white).into_iter()
+        .min_by_key(|fromc| read_board(board, *fromc).map_or(0, worth))?;
+
+    if let Some(King(_, _)) = read_board(board, fromc) {
+        let mut after = *board;
+        write_board(&mut after, fromc, Open);
+        write_board(&mut after, toc, King(is_white, true));
+        if !attackers(&after, toc, is_white).is_empty() {
+            return None;
+        }
+    }
+    Some(fromc)
+}
+
+// what the side making the move comes out with, in centipawns. a quiet move onto a square the opponent
+// can win the piece on counts as losing it, and one that's safe is worth nothing either way.
+pub fn see(game: &Game, play: Play) -> i32 {
+    let mut board = game.board;
+
+    let (mover, toc, gain) = match play {
+        Play::Move(fromc, toc, promotion) => {
+            let mover = match read_board(&board, fromc) {
+                Some(piece) if piece != Open => piece,
+                _ => {return 0;}
+            };
+            let mut gain = match read_board(&board, toc) {
+                // castling onto its own rook, in Chess960.
+                Some(piece) if piece != Open && piece.is_white() == mover.is_white() => {return 0;},
+                Some(piece) => worth(piece),
+                None => {return 0;}
+            };
+            // en passant takes a pawn from beside the square moved to.
+            if let (Pawn(w, _), Some(Open)) = (mover, read_board(&board, toc)) {
+                if fromc[0] != toc[0] {
+                    write_board(&mut board, [toc[0], fromc[1]], Open);
+                    gain = worth(Pawn(!w, PawnState::Moved));
+                }
+            }
+            write_board(&mut board, fromc, Open);
+
+            let mover = match game.variant.promotions_at(&game.board, fromc, toc).contains(&promotion) {
+                true => {
+                    let promoted = promotion.piece(mover.is_white());
+                    gain += worth(promoted) - worth(mover);
+                    promoted
+                },
+                false => mover
+            };
+            (mover, toc, gain)
+        },
+        Play::Drop(kind, toc) => (pocket::piece(&board, kind, game.turn_white, toc), toc, 0)
+    };
+    write_board(&mut board, toc, mover);
+
+    // gains[n] is what the side making the nth capture wins if it stops there.
+    let mut gains = vec![gain];
+    let mut is_white = !mover.is_white();
+    while let Some(fromc) = least_valuable(&board, toc, is_white) {
+        let taken = read_board(&board, toc).map_or(0, worth);
+        gains.push(taken - gains[gains.len() - 1]);
+
+        let piece = read_board(&board, fromc).unwrap_or(Open);
+        let piece = arrives(&board, piece, toc);
+        write_board(&mut board, fromc, Open);
+        write_board(&mut board, toc, piece);
+        is_white = !is_white;
+    }
+
+    // each side only takes when it's better than stopping.
+    while gains.len() > 1 {
+        let last = gains.pop().unwrap();
+        let before = gains.last_mut().unwrap();
+        *before = -(-*before).max(last);
+    }
+    gains[0]
+}
+
+// the pieces of the given team that the other one can win material by taking, most valuable first.
+pub fn hanging(game: &Game, is_white: bool) -> Vec<Loc> {
+    let board = &game.board;
+    let mut hanging = Vec::new();
+
+    for y in 1..=board.height {
+        for x in 1..=board.width {
+            let piece = match read_board(board, [x, y]) {
+                Some(piece) if piece != Open && piece.is_white() == is_white && !matches!(piece, King(_, _)) => piece,
+                _ => {continue;}
+            };
+            let wins = attackers(board, [x, y], is_white).into_iter().any(|fromc| {
+                see(game, Play::Move(fromc, [x, y], game.variant.promotions()[0])) > 0
+            });
+            if wins {
+                hanging.push(([x, y], piece));
+            }
+        }
+    }
+    hanging.sort_by_key(|(_, piece)| -worth(*piece));
+    hanging.into_iter().map(|(loc, _)| loc).collect()
+}
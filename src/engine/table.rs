@@ -0,0 +1,227 @@
+use std::mem::size_of;
+use crate::{
+    board::MAX_SIZE,
+    game::{Game, side},
+    types::{PawnState, Space, Space::*}
+};
+use super::{Play, search::MATE_BOUND};
+
+
+// remembers what the search found out about positions it has seen, so reaching one again
+// by a different order of moves doesn't mean searching it all over again.
+
+// a number for everything about a position that matters to the search, XORed together.
+// each feature's number comes from mixing up a description of it, which is as good as a table of random numbers.
+fn mix(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+// a different number for every kind of piece in every state it can be in, for either side.
+fn code(piece: Space) -> u64 {
+    let kind = match piece {
+        Pawn(_, PawnState::NotMoved) => 0,
+        Pawn(_, PawnState::PrevSkipped) => 1,
+        Pawn(_, PawnState::Moved) => 2,
+        Rook(_, moved) => 3 + moved as u64,
+        Knight(_) => 5,
+        Bishop(_) => 6,
+        Queen(_) => 7,
+        King(_, moved) => 8 + moved as u64,
+        Archbishop(_) => 10,
+        Chancellor(_) => 11,
+        Fairy(_, index, moved) => 12 + index as u64 * 2 + moved as u64,
+        Open => {return 0;}
+    };
+    kind * 2 + piece.is_white() as u64
+}
+
+// which sort of feature a number is for, kept apart in the top bits.
+const SQUARE: u64 = 1 << 60;
+const TURN: u64 = 2 << 60;
+const POCKET: u64 = 3 << 60;
+const CHECKS: u64 = 4 << 60;
+const PROMOTED: u64 = 5 << 60;
+
+pub fn hash(game: &Game) -> u64 {
+    let board = &game.board;
+    let mut hash = if game.turn_white {0} else {mix(TURN)};
+
+    for y in 1..=board.height {
+        for x in 1..=board.width {
+            let square = ((y - 1) * MAX_SIZE as isize + x - 1) as u64;
+            let piece = board.squares[y as usize - 1][x as usize - 1];
+            if piece != Open {
+                hash ^= mix(SQUARE | code(piece) << 8 | square);
+            }
+            if game.is_promoted([x, y]) {
+                hash ^= mix(PROMOTED | square);
+            }
+        }
+    }
+
+    for is_white in [true, false] {
+        for (kind, count) in game.pockets[side(is_white)].iter().enumerate() {
+            if *count > 0 {
+                hash ^= mix(POCKET | (is_white as u64) << 16 | (kind as u64) << 8 | *count as u64);
+            }
+        }
+        let checks = game.checks[side(is_white)];
+        if checks > 0 {
+            hash ^= mix(CHECKS | (is_white as u64) << 8 | checks as u64);
+        }
+    }
+    hash
+}
+
+
+// what a stored score means: the real score, or just that it's at least or at most that.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    Lower,
+    Upper
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Entry {
+    key: u64,
+    pub depth: u32,
+    pub bound: Bound,
+    // mates are stored as so many moves from this position, not from where the search started.
+    score: i32,
+    pub play: Option<Play>,
+    // which search stored it, so ones left over from earlier moves give way first.
+    age: u8
+}
+
+// mate scores count the moves from the start of the search, which depends on how the position was reached.
+// in the table they count from the position itself instead.
+
+fn to_table(score: i32, ply: u32) -> i32 {
+    match score {
+        s if s > MATE_BOUND => s + ply as i32,
+        s if s < -MATE_BOUND => s - ply as i32,
+        s => s
+    }
+}
+
+fn from_table(score: i32, ply: u32) -> i32 {
+    match score {
+        s if s > MATE_BOUND => s - ply as i32,
+        s if s < -MATE_BOUND => s + ply as i32,
+        s => s
+    }
+}
+
+impl Entry {
+    pub fn score(&self, ply: u32) -> i32 {
+        from_table(self.score, ply)
+    }
+}
+
+// how often the table has been of use, for tuning its size.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub probes: u64,
+    pub hits: u64,
+    pub stores: u64,
+    // stores that pushed out a different position.
+    pub replaced: u64
+}
+
+impl Stats {
+    pub fn hit_rate(&self) -> f64 {
+        match self.probes {
+            0 => 0.0,
+            probes => self.hits as f64 / probes as f64
+        }
+    }
+}
+
+// each position can go in either of a couple of slots, which gives the replacement policy something to choose between.
+const BUCKET: usize = 2;
+
+pub struct Table {
+    slots: Vec<Option<Entry>>,
+    age: u8,
+    pub stats: Stats
+}
+
+impl Table {
+
+    pub const DEFAULT_MB: usize = 16;
+
+    pub fn new(mb: usize) -> Table {
+        let buckets = (mb * 1024 * 1024 / (size_of::<Option<Entry>>() * BUCKET)).max(1);
+        Table { slots: vec![None; buckets * BUCKET], age: 0, stats: Stats::default() }
+    }
+
+    // how many positions it has room for.
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    // forgets everything, for a new game.
+    pub fn clear(&mut self) {
+        self.slots.fill(None);
+        self.stats = Stats::default();
+    }
+
+    // called at the start of every search, so what earlier searches left behind can be told apart.
+    pub fn new_search(&mut self) {
+        self.age = self.age.wrapping_add(1);
+    }
+
+    fn bucket(&self, key: u64) -> std::ops::Range<usize> {
+        let start = (key % (self.len() / BUCKET) as u64) as usize * BUCKET;
+        start..start + BUCKET
+    }
+
+    // looks a position up without it counting towards the statistics.
+    pub fn peek(&self, key: u64) -> Option<Entry> {
+        self.slots[self.bucket(key)].iter().flatten().find(|entry| entry.key == key).copied()
+    }
+
+    pub fn probe(&mut self, key: u64) -> Option<Entry> {
+        self.stats.probes += 1;
+        let found = self.peek(key);
+        if found.is_some() {
+            self.stats.hits += 1;
+        }
+        found
+    }
+
+    // goes in place of the same position, or an empty slot, or else whatever is oldest and shallowest.
+    pub fn store(&mut self, key: u64, depth: u32, bound: Bound, score: i32, ply: u32, play: Option<Play>) {
+        let age = self.age;
+        let worth = |slot: &Option<Entry>| match slot {
+            None => i32::MIN,
+            Some(entry) if entry.key == key => i32::MIN,
+            Some(entry) => entry.depth as i32 - 8 * age.wrapping_sub(entry.age) as i32
+        };
+
+        let range = self.bucket(key);
+        let victim = range.clone().min_by_key(|i| worth(&self.slots[*i])).unwrap_or(range.start);
+        let slot = &mut self.slots[victim];
+
+        // a shallower look at the same position shouldn't lose the best move a deeper one found.
+        let play = match slot {
+            Some(entry) if entry.key == key && play.is_none() => entry.play,
+            _ => play
+        };
+        if slot.is_some_and(|entry| entry.key != key) {
+            self.stats.replaced += 1;
+        }
+        self.stats.stores += 1;
+        *slot = Some(Entry { key, depth, bound, score: to_table(score, ply), play, age });
+    }
+
+    // how full the table is, in thousandths, going by the first thousand slots.
+    pub fn fill(&self) -> usize {
+        let sample = self.len().min(1000);
+        self.slots[..sample].iter().filter(|slot| slot.is_some_and(|entry| entry.age == self.age)).count() * 1000 / sample
+    }
+}
@@ -0,0 +1,148 @@
+use std::{sync::{Arc, atomic::{AtomicBool, Ordering}}, time::{Duration, Instant}};
+use crate::{clock::Clock, game::side};
+use super::Play;
+
+
+// how long the search may go on for, and when to call it a day.
+// with no limits at all it keeps going until it's told to stop, or can't look any deeper.
+
+#[derive(Debug, Default)]
+struct Flags {
+    stopped: AtomicBool,
+    pondering: AtomicBool
+}
+
+// handed to the search and kept by whoever started it, so another thread can stop it.
+// the search then gives back the best move it had found.
+// it also says when the search is thinking on the opponent's time, when its own clock hasn't started yet.
+#[derive(Clone, Debug, Default)]
+pub struct Stop(Arc<Flags>);
+
+impl Stop {
+    pub fn stop(&self) {
+        self.0.stopped.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.0.stopped.load(Ordering::Relaxed)
+    }
+
+    pub fn ponder(&self) {
+        self.0.pondering.store(true, Ordering::Relaxed);
+    }
+
+    // the opponent played the move it was thinking about, so the time it takes from here on is its own.
+    pub fn ponder_hit(&self) {
+        self.0.pondering.store(false, Ordering::Relaxed);
+    }
+
+    pub fn is_pondering(&self) -> bool {
+        self.0.pondering.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Limits {
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    // exactly this long to think.
+    pub movetime: Option<Duration>,
+    // each side's time left and what they get back per move, indexed by side(), and how many moves there are
+    // until the next time control, if there is one. the time manager works out how much of it to spend.
+    pub time: [Option<Duration>; 2],
+    pub increment: [Duration; 2],
+    pub moves_to_go: Option<u32>,
+    // only these moves are looked at, unless it's empty.
+    pub moves: Vec<Play>,
+    pub stop: Stop
+}
+
+impl Limits {
+
+    pub fn depth(depth: u32) -> Limits {
+        Limits { depth: Some(depth), ..Limits::default() }
+    }
+
+    // playing to the game's own clock.
+    pub fn clock(clock: &Clock) -> Limits {
+        Limits {
+            time: [Some(clock.time_left(true)), Some(clock.time_left(false))],
+            increment: [clock.increment(); 2],
+            ..Limits::default()
+        }
+    }
+}
+
+// what the engine allows for reading the clock, sending the move and so on, so it doesn't lose on time doing it.
+const OVERHEAD: Duration = Duration::from_millis(30);
+// how many moves the time left is shared between when nobody says.
+const MOVES_TO_GO: u32 = 30;
+
+// the search is given a soft limit, after which it doesn't start looking another move deeper,
+// and a hard one, at which it stops whatever it's doing. the soft limit stretches when the search
+// keeps changing its mind, since that's when more time is most worth it.
+pub struct TimeManager {
+    start: Instant,
+    soft: Option<Duration>,
+    hard: Option<Duration>,
+    // how much the soft limit has been stretched by.
+    stretch: f64,
+    // thinking on the opponent's time, there's no hurry until they play the expected move.
+    pondering: bool,
+    stop: Stop
+}
+
+impl TimeManager {
+
+    pub fn new(limits: &Limits, is_white: bool) -> TimeManager {
+        let mut soft = limits.movetime;
+        let mut hard = limits.movetime;
+
+        if let Some(left) = limits.time[side(is_white)] {
+            let moves = limits.moves_to_go.unwrap_or(MOVES_TO_GO).clamp(1, MOVES_TO_GO);
+            let usable = left.saturating_sub(OVERHEAD);
+            let share = usable / moves + limits.increment[side(is_white)] * 3 / 4;
+            // with the time control about to come round there's no point saving any, but otherwise a move
+            // that goes badly still leaves plenty for the rest.
+            let most = if moves == 1 {usable.mul_f64(0.9)} else {usable / 2};
+
+            soft = Some(soft.map_or(share, |soft| soft.min(share)).min(most));
+            hard = Some(hard.map_or(share * 4, |hard| hard.min(share * 4)).min(most));
+        }
+
+        TimeManager { start: Instant::now(), soft, hard, stretch: 1.0, pondering: limits.stop.is_pondering(), stop: limits.stop.clone() }
+    }
+
+    // whether its own clock is running, which it starts doing the moment pondering ends.
+    fn running(&mut self) -> bool {
+        if self.pondering && !self.stop.is_pondering() {
+            self.pondering = false;
+            self.start = Instant::now();
+        }
+        !self.pondering
+    }
+
+    // whether the search has to stop right now.
+    pub fn out_of_time(&mut self) -> bool {
+        self.running() && self.hard.is_some_and(|hard| self.start.elapsed() >= hard)
+    }
+
+    // whether there's time to look another move deeper. each one takes a few times as long as the last,
+    // so it isn't started past half way to the soft limit.
+    pub fn another(&mut self) -> bool {
+        match (self.running(), self.soft, self.hard) {
+            (true, Some(soft), Some(hard)) => self.start.elapsed() < soft.mul_f64(self.stretch).min(hard) / 2,
+            _ => true
+        }
+    }
+
+    // the best move changed, or the score fell, so it's worth thinking on a bit longer.
+    pub fn unstable(&mut self) {
+        self.stretch = (self.stretch * 1.5).min(3.0);
+    }
+
+    // the same move has stayed best for a while, so some of the extra time can go back.
+    pub fn stable(&mut self) {
+        self.stretch = (self.stretch * 0.9).max(1.0);
+    }
+}
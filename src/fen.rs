@@ -0,0 +1,416 @@
+use std::fmt;
+use crate::{
+    board::{Board, Loc, MAX_SIZE, read_board, write_board},
+    game::{Game, side},
+    piece::castle_rook,
+    pocket::{self, Pocket},
+    variant::{Standard, ThreeCheck, Variant},
+    types::{Direction, PawnState, Space, Space::*}
+};
+
+
+// turns an in game location into a square name like "e4".
+// ranks count up from the bottom, so it needs to know how many the board has.
+pub fn square_name(height: isize, loc: Loc) -> String {
+    format!("{}{}", (b'a' + (loc[0] - 1) as u8) as char, height + 1 - loc[1])
+}
+
+// the other way around; None if it isn't a square on a board that big.
+pub fn parse_square(height: isize, name: &str) -> Option<Loc> {
+    let file = name.chars().next()?;
+    let rank = name.get(1..)?;
+    if !('a'..='j').contains(&file) || !rank.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    match rank.parse::<isize>() {
+        Ok(rank) if (1..=height).contains(&rank) => Some([(file as u8 - b'a') as isize + 1, height + 1 - rank]),
+        _ => None
+    }
+}
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FenError {
+    Fields,
+    Placement,
+    Turn,
+    Castling,
+    EnPessant,
+    Counter,
+    Checks,
+    Pocket
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use FenError::*;
+        f.write_str(match self {
+            Fields => "a FEN needs at least the piece placement and the side to move",
+            Placement => "the piece placement isn't ranks of the same length with one king each",
+            Turn => "the side to move must be 'w' or 'b'",
+            Castling => "the castling field doesn't match the kings and rooks on the board",
+            EnPessant => "the en passant square doesn't have a pawn that just skipped past it",
+            Counter => "the move counters must be numbers",
+            Checks => "the check counters must look like '+1+0' or '3+3'",
+            Pocket => "the pocket can only hold pawns, knights, bishops, rooks and queens"
+        })
+    }
+}
+
+
+fn piece_char(piece: Space) -> char {
+    let c: char = piece.into();
+    match piece.is_white() {
+        true => c,
+        false => c.to_ascii_lowercase()
+    }
+}
+
+// the home rank of either side.
+fn back_rank_y(board: &Board, is_white: bool) -> isize {
+    if is_white {board.height} else {1}
+}
+
+// where a king that could still castle is; it has to be on its own back rank.
+fn castling_king(board: &Board, is_white: bool) -> Option<Loc> {
+    let y = back_rank_y(board, is_white);
+    (1..=board.width).map(|x| [x, y]).find(|loc| matches!(read_board(board, *loc), Some(King(w, _)) if w == is_white))
+}
+
+// the rook furthest out towards 'dir' on the king's rank, which is the one 'K' and 'Q' stand for.
+fn outer_rook(board: &Board, kingc: Loc, is_white: bool, dir: Direction) -> Option<Loc> {
+    let mut outer = None;
+    for n in 1.. {
+        let testc = dir.translate(kingc, n);
+        match read_board(board, testc) {
+            Some(Rook(w, _)) if w == is_white => {outer = Some(testc);},
+            Some(_) => {},
+            None => {break;}
+        }
+    }
+    outer
+}
+
+// X-FEN uses 'K' and 'Q' like normal FEN, and only falls back on the rook's file letter
+// when there is another rook further out that side. Shredder-FEN, which always uses file letters, is only read.
+fn castling_field(board: &Board) -> String {
+
+    let mut field = String::new();
+    for is_white in [true, false] {
+
+        let king = match castling_king(board, is_white) {
+            Some(king) if read_board(board, king) == Some(King(is_white, false)) => king,
+            _ => {continue;}
+        };
+
+        for (dir, c) in [(Direction::East, 'K'), (Direction::West, 'Q')] {
+            if let Some(rook) = castle_rook(board, king, is_white, dir) {
+                let c = match outer_rook(board, king, is_white, dir) == Some(rook) {
+                    true => c,
+                    false => (b'A' + (rook[0] - 1) as u8) as char
+                };
+                field.push(if is_white {c} else {c.to_ascii_lowercase()});
+            }
+        }
+    }
+
+    if field.is_empty() {
+        field.push('-');
+    }
+    field
+}
+
+// the square a pawn skipped over on the last move, if there was one.
+fn en_pessant_square(board: &Board, turn_white: bool) -> Option<Loc> {
+    for x in 1..=board.width {
+        for y in 1..=board.height {
+            if let Some(Pawn(w, PawnState::PrevSkipped)) = read_board(board, [x, y]) {
+                if w != turn_white {
+                    let behind = if w {Direction::North} else {Direction::South};
+                    return Some(behind.translate([x, y], 1));
+                }
+            }
+        }
+    }
+    None
+}
+
+pub fn to_fen(game: &Game) -> String {
+
+    let board = &game.board;
+    let mut ranks = Vec::new();
+
+    let pockets = game.variant.has_pockets();
+
+    for y in 1..=board.height {
+        let mut rank = String::new();
+        let mut empty = 0;
+
+        for x in 1..=board.width {
+            let space = read_board(board, [x, y]).unwrap_or(Open);
+            if space == Open {
+                empty += 1;
+                continue;
+            }
+            if empty > 0 {
+                rank.push_str(&empty.to_string());
+                empty = 0;
+            }
+            rank.push(piece_char(space));
+
+            // Crazyhouse marks promoted pieces, since they go back to being pawns when taken.
+            if pockets && game.is_promoted([x, y]) {
+                rank.push('~');
+            }
+        }
+        if empty > 0 {
+            rank.push_str(&empty.to_string());
+        }
+        ranks.push(rank);
+    }
+
+    let mut placement = ranks.join("/");
+    if pockets {
+        placement.push_str(&format!("[{}]", pocket_field(&game.pockets)));
+    }
+
+    let mut fen = format!("{} {} {} {} {} {}",
+        placement,
+        if game.turn_white {"w"} else {"b"},
+        if game.variant.castles() {castling_field(board)} else {"-".to_string()},
+        en_pessant_square(board, game.turn_white).map(|loc| square_name(board.height, loc)).unwrap_or("-".to_string()),
+        game.halfmove_clock,
+        game.fullmove
+    );
+
+    // the checks given so far go on the end, the way Stockfish writes them.
+    if game.variant.counts_checks() {
+        fen.push_str(&format!(" +{}+{}", game.checks[0], game.checks[1]));
+    }
+    fen
+}
+
+// the pieces in both pockets, white's in capitals first.
+fn pocket_field(pockets: &[Pocket; 2]) -> String {
+    let mut field = String::new();
+    for is_white in [true, false] {
+        for (kind, c) in pocket::KINDS.iter().enumerate() {
+            for _ in 0..pockets[side(is_white)][kind] {
+                field.push(if is_white {*c} else {c.to_ascii_lowercase()});
+            }
+        }
+    }
+    field
+}
+
+// reads "1+0" into the checks white and black have given,
+// or have left to give if 'remaining' is set.
+fn check_counters(field: &str, remaining: bool) -> Result<[u8; 2], FenError> {
+    let mut checks = [0; 2];
+    let mut parts = field.split('+');
+
+    for count in checks.iter_mut() {
+        let n = parts.next().and_then(|part| part.parse::<u8>().ok()).ok_or(FenError::Checks)?;
+        if n > ThreeCheck::CHECKS {
+            return Err(FenError::Checks);
+        }
+        *count = if remaining {ThreeCheck::CHECKS - n} else {n};
+    }
+
+    match parts.next() {
+        Some(_) => Err(FenError::Checks),
+        None => Ok(checks)
+    }
+}
+
+
+// spells out one rank of the placement, a square at a time, with empty squares as None.
+// runs of ten or more empty squares take two digits, and a '~' after a piece marks it as promoted.
+fn expand_rank(rank: &str) -> Result<Vec<Option<(char, bool)>>, FenError> {
+    let mut squares = Vec::new();
+    let mut chars = rank.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if let Some(n) = c.to_digit(10) {
+            let mut n = n as usize;
+            while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+                n = n * 10 + digit as usize;
+                chars.next();
+            }
+            if n == 0 {
+                return Err(FenError::Placement);
+            }
+            squares.extend(std::iter::repeat_n(None, n));
+            continue;
+        }
+
+        let promoted = chars.peek() == Some(&'~');
+        if promoted {
+            chars.next();
+        }
+        squares.push(Some((c, promoted)));
+    }
+    Ok(squares)
+}
+
+// reads a FEN into a fresh game of standard chess without a clock.
+pub fn from_fen(fen: &str) -> Result<Game, FenError> {
+    from_variant_fen(&Standard, fen)
+}
+
+// the castling and en passant fields decide which kings, rooks and pawns count as unmoved.
+pub fn from_variant_fen(variant: &'static dyn Variant, fen: &str) -> Result<Game, FenError> {
+
+    let mut fields: Vec<&str> = fen.split_whitespace().collect();
+    if fields.len() < 2 {
+        return Err(FenError::Fields);
+    }
+
+    // Three-check counters come either as the checks given at the very end ("+1+0"),
+    // or as lichess's checks left to give straight after the en passant square ("2+3").
+    let mut checks = [0, 0];
+    if fields.len() > 2 && fields[fields.len() - 1].starts_with('+') {
+        checks = check_counters(&fields.pop().unwrap()[1..], false)?;
+    } else if fields.len() > 4 && fields[4].contains('+') {
+        checks = check_counters(fields.remove(4), true)?;
+    }
+
+    // Crazyhouse pockets follow the placement, either in brackets or as a rank past the last one.
+    // taller boards have ranks of their own there, so it only counts as a pocket when the variant has them.
+    // variants with a start of their own read it from a FEN, so it's only looked at where there are pockets.
+    let pocket_rank = || variant.has_pockets() && fields[0].split('/').count() == variant.start().map_or(8, |board| board.height as usize) + 1;
+    let (placement, pocket_chars) = match fields[0].split_once('[') {
+        Some((placement, rest)) => (placement, Some(rest.strip_suffix(']').ok_or(FenError::Pocket)?)),
+        None => match fields[0].rsplit_once('/') {
+            Some((placement, pocket)) if pocket_rank() => (placement, Some(pocket)),
+            _ => (fields[0], None)
+        }
+    };
+
+    let mut pockets = [[0; 5]; 2];
+    for c in pocket_chars.unwrap_or("").chars() {
+        let kind = pocket::KINDS.iter().position(|kind| *kind == c.to_ascii_uppercase()).ok_or(FenError::Pocket)?;
+        pockets[side(c.is_ascii_uppercase())][kind] += 1;
+    }
+
+    // the board is as wide as its ranks and as tall as there are of them.
+    let ranks = placement.split('/').map(expand_rank).collect::<Result<Vec<_>, _>>()?;
+    let (width, height) = (ranks[0].len(), ranks.len());
+    if !(1..=MAX_SIZE).contains(&width) || height > MAX_SIZE || ranks.iter().any(|rank| rank.len() != width) {
+        return Err(FenError::Placement);
+    }
+
+    let mut board = Board::empty(width as isize, height as isize);
+    let mut promoted = Vec::new();
+
+    for (row, rank) in ranks.iter().enumerate() {
+        let y = row as isize + 1;
+
+        for (column, square) in rank.iter().enumerate() {
+            let x = column as isize + 1;
+            let (c, was_promoted) = match square {
+                Some(square) => *square,
+                None => {continue;}
+            };
+            if was_promoted {
+                promoted.push([x, y]);
+            }
+
+            let w = c.is_ascii_uppercase();
+            // pawns on or behind their starting rank haven't moved yet,
+            // which takes in Horde pawns on the first rank.
+            let pawn_rank = variant.pawn_rank();
+            let pawn_state = match w {
+                true if y > board.height - pawn_rank => PawnState::NotMoved,
+                false if y <= pawn_rank => PawnState::NotMoved,
+                _ => PawnState::Moved
+            };
+
+            let piece = match c.to_ascii_uppercase() {
+                'P' => Pawn(w, pawn_state),
+                'R' => Rook(w, true),
+                'N' => Knight(w),
+                'B' => Bishop(w),
+                'Q' => Queen(w),
+                'K' => King(w, true),
+                'A' => Archbishop(w),
+                'C' => Chancellor(w),
+                // made up pieces haven't moved if they're where a pawn would start.
+                letter => match variant.fairy(letter) {
+                    Some(index) => Fairy(w, index, pawn_state == PawnState::Moved),
+                    None => {return Err(FenError::Placement);}
+                }
+            };
+            write_board(&mut board, [x, y], piece);
+        }
+    }
+
+    if !variant.valid(&board) {
+        return Err(FenError::Placement);
+    }
+
+    let turn_white = match fields[1] {
+        "w" => true,
+        "b" => false,
+        _ => {return Err(FenError::Turn);}
+    };
+
+    // 'K' and 'Q' mean the outermost rook on that side, and a file letter means the rook on that file,
+    // so both X-FEN and Shredder-FEN read the same way.
+    let castling = fields.get(2).copied().unwrap_or("-");
+    if castling != "-" && variant.castles() {
+        for c in castling.chars() {
+
+            let is_white = c.is_ascii_uppercase();
+            let king = castling_king(&board, is_white).ok_or(FenError::Castling)?;
+            let rook = match c.to_ascii_uppercase() {
+                'K' => outer_rook(&board, king, is_white, Direction::East),
+                'Q' => outer_rook(&board, king, is_white, Direction::West),
+                file @ 'A'..='J' => Some([(file as u8 - b'A') as isize + 1, king[1]]),
+                _ => {return Err(FenError::Castling);}
+            };
+
+            let rook = rook.ok_or(FenError::Castling)?;
+            match read_board(&board, rook) {
+                Some(Rook(w, _)) if w == is_white => {},
+                _ => {return Err(FenError::Castling);}
+            }
+            write_board(&mut board, king, King(is_white, false));
+            write_board(&mut board, rook, Rook(is_white, false));
+        }
+    }
+
+    let en_pessant = fields.get(3).copied().unwrap_or("-");
+    if en_pessant != "-" {
+        let square = parse_square(board.height, en_pessant).ok_or(FenError::EnPessant)?;
+
+        // the pawn that skipped belongs to the side that just moved, one step past the square.
+        let ahead = if turn_white {Direction::North} else {Direction::South};
+        let pawnc = ahead.translate(square, 1);
+        match read_board(&board, pawnc) {
+            Some(Pawn(w, _)) if w != turn_white => {
+                write_board(&mut board, pawnc, Pawn(w, PawnState::PrevSkipped));
+            },
+            _ => {return Err(FenError::EnPessant);}
+        }
+    }
+
+    let counter = |index: usize, default: u32| match fields.get(index) {
+        Some(field) => field.parse::<u32>().map_err(|_| FenError::Counter),
+        None => Ok(default)
+    };
+
+    let mut game = Game::new(variant, board, None);
+    game.turn_white = turn_white;
+    game.checks = checks;
+    game.pockets = pockets;
+    for loc in promoted {
+        if read_board(&game.board, loc).is_none() {
+            return Err(FenError::Placement);
+        }
+        game.set_promoted(loc, true);
+    }
+    game.halfmove_clock = counter(4, 0)?;
+    game.fullmove = counter(5, 1)?;
+    Ok(game)
+}
@@ -0,0 +1,243 @@
+use std::{fmt, mem::discriminant};
+use crate::{
+    board::{Board, Loc, MAX_SIZE, do_move, move_piece, read_board},
+    clock::{Clock, TimeControl},
+    pocket::{self, Pocket, drop_check},
+    types::{IllegalMove, Movement::Drop, Promotion, Space::*},
+    variant::Variant
+};
+
+
+// where a team's entry goes in the pairs of counters kept for both sides.
+pub fn side(is_white: bool) -> usize {
+    if is_white {0} else {1}
+}
+
+pub fn team_name(is_white: bool) -> &'static str {
+    match is_white {
+        true => "White",
+        false => "Black"
+    }
+}
+
+// how a game came to an end. the bools hold the winning team.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Checkmate(bool),
+    Stalemate,
+    Timeout(bool),
+    Hill(bool),
+    ThirdCheck(bool),
+    Exploded(bool),
+    OutOfPieces(bool),
+    OutOfMoves(bool),
+    HordeTaken,
+    KingHome(bool),
+    BothKingsHome,
+    KingTaken(bool),
+    OtherBoard(bool)
+}
+
+impl Outcome {
+    pub fn winner(&self) -> Option<bool> {
+        match *self {
+            Outcome::Stalemate | Outcome::BothKingsHome => None,
+            Outcome::HordeTaken => Some(false),
+            Outcome::Checkmate(w) | Outcome::Timeout(w) | Outcome::Hill(w) | Outcome::ThirdCheck(w) | Outcome::Exploded(w)
+                | Outcome::OutOfPieces(w) | Outcome::OutOfMoves(w) | Outcome::KingHome(w) | Outcome::KingTaken(w)
+                | Outcome::OtherBoard(w) => Some(w)
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Checkmate(w) => write!(f, "Checkmate! {} has won!", team_name(*w)),
+            Outcome::Stalemate => write!(f, "Stalemate. The game is drawn."),
+            Outcome::Timeout(w) => write!(f, "{} ran out of time. {} has won!", team_name(!*w), team_name(*w)),
+            Outcome::Hill(w) => write!(f, "{}'s king reached the hill. {} has won!", team_name(*w), team_name(*w)),
+            Outcome::ThirdCheck(w) => write!(f, "Third check! {} has won!", team_name(*w)),
+            Outcome::Exploded(w) => write!(f, "{}'s king was blown up. {} has won!", team_name(!*w), team_name(*w)),
+            Outcome::OutOfPieces(w) => write!(f, "{} has nothing left to lose. {} has won!", team_name(*w), team_name(*w)),
+            Outcome::OutOfMoves(w) => write!(f, "{} can't move. {} has won!", team_name(*w), team_name(*w)),
+            Outcome::HordeTaken => write!(f, "The whole horde has been taken. Black has won!"),
+            Outcome::KingHome(w) => write!(f, "{}'s king reached the eighth rank. {} has won!", team_name(*w), team_name(*w)),
+            Outcome::BothKingsHome => write!(f, "Both kings reached the eighth rank. The game is drawn."),
+            Outcome::KingTaken(w) => write!(f, "{}'s king was taken. {} has won!", team_name(!*w), team_name(*w)),
+            Outcome::OtherBoard(w) => write!(f, "The other board has finished. {} and their partner have won!", team_name(*w))
+        }
+    }
+}
+
+
+// everything about a game in progress that isn't to do with the interface.
+#[derive(Clone)]
+pub struct Game {
+    pub variant: &'static dyn Variant,
+    pub board: Board,
+    pub turn_white: bool,
+    // moves since the last capture or pawn move, and the number of the current move.
+    pub halfmove_clock: u32,
+    pub fullmove: u32,
+    pub clock: Option<Clock>,
+    // how many times white and black have given check.
+    pub checks: [u8; 2],
+    // the pieces white and black have captured and can drop, in variants that have pockets.
+    pub pockets: [Pocket; 2],
+    // squares holding a piece that started out as a pawn, which goes back to being a pawn when captured.
+    pub promoted: [[bool; MAX_SIZE]; MAX_SIZE],
+    // where the last move took something, if it did.
+    pub last_capture: Option<Loc>,
+    // pieces taken by each side that are on their way to the partner's pocket, in Bughouse.
+    pub passing: Vec<(bool, usize)>,
+    pub outcome: Option<Outcome>
+}
+
+impl Game {
+
+    pub fn new(variant: &'static dyn Variant, board: Board, time_control: Option<TimeControl>) -> Game {
+        Game {
+            variant,
+            board,
+            turn_white: true,
+            halfmove_clock: 0,
+            fullmove: 1,
+            clock: time_control.map(Clock::new),
+            checks: [0, 0],
+            pockets: [[0; 5]; 2],
+            promoted: [[false; MAX_SIZE]; MAX_SIZE],
+            last_capture: None,
+            passing: Vec::new(),
+            outcome: None
+        }
+    }
+
+    // plays a move for the side whose turn it is, then checks if the game is over.
+    // pawns that reach the far end become whatever the variant likes best.
+    pub fn play(&mut self, fromc: Loc, toc: Loc) -> Result<(), IllegalMove> {
+        self.play_promoting(fromc, toc, self.promotion(fromc, toc))
+    }
+
+    // what a pawn moving from 'fromc' to 'toc' becomes if nobody says: the variant's favourite of what it can pick from there.
+    pub fn promotion(&self, fromc: Loc, toc: Loc) -> Promotion {
+        self.variant.promotions_at(&self.board, fromc, toc).first().copied().unwrap_or(self.variant.promotions()[0])
+    }
+
+    pub fn play_promoting(&mut self, fromc: Loc, toc: Loc, promotion: Promotion) -> Result<(), IllegalMove> {
+
+        // castling onto your own rook isn't a capture.
+        let resets = matches!(read_board(&self.board, fromc), Some(Pawn(_, _)))
+            || matches!(read_board(&self.board, toc), Some(piece) if piece != Open && piece.is_white() != self.turn_white);
+
+        let before = self.board;
+        move_piece(self.variant, &mut self.board, fromc, toc, self.turn_white, promotion)?;
+        self.track_captures(&before, fromc, toc);
+        self.finish_move(resets);
+        Ok(())
+    }
+
+    // drops a piece of 'kind' from the pocket of the side whose turn it is.
+    pub fn drop(&mut self, kind: usize, toc: Loc) -> Result<(), IllegalMove> {
+
+        let pocket = &mut self.pockets[side(self.turn_white)];
+        drop_check(&self.board, pocket, kind, self.turn_white, toc)?;
+
+        let piece = pocket::piece(&self.board, kind, self.turn_white, toc);
+        do_move(&mut self.board, toc, piece, toc, Drop);
+        pocket[kind] -= 1;
+        self.last_capture = None;
+
+        // dropping a pawn counts as a pawn move.
+        self.finish_move(kind == 0);
+        Ok(())
+    }
+
+    // captured pieces go into the capturing side's pocket, as pawns if they were promoted,
+    // and the promoted marks follow the pieces around.
+    fn track_captures(&mut self, before: &Board, fromc: Loc, toc: Loc) {
+
+        let promotes = matches!(read_board(before, fromc), Some(Pawn(_, _)))
+            && !matches!(read_board(&self.board, toc), Some(Pawn(_, _)));
+        let promoted = self.is_promoted(fromc) || promotes;
+        self.last_capture = None;
+
+        for y in 1..=before.height {
+            for x in 1..=before.width {
+                let piece = match read_board(before, [x, y]) {
+                    Some(Open) | None => {continue;},
+                    Some(piece) => piece
+                };
+                // a pawn that skipped last turn changes state without going anywhere.
+                let still_there = match read_board(&self.board, [x, y]) {
+                    Some(Open) | None => false,
+                    Some(now) => now.is_white() == piece.is_white() && discriminant(&now) == discriminant(&piece)
+                };
+                if piece.is_white() == self.turn_white || still_there {
+                    continue;
+                }
+                self.last_capture = Some(toc);
+
+                if self.variant.has_pockets() {
+                    let kind = match self.is_promoted([x, y]) {
+                        true => Some(0),
+                        false => pocket::kind(piece)
+                    };
+                    match (kind, self.variant.passes_captures()) {
+                        (Some(kind), true) => self.passing.push((self.turn_white, kind)),
+                        (Some(kind), false) => self.pockets[side(self.turn_white)][kind] += 1,
+                        (None, _) => ()
+                    }
+                }
+                self.set_promoted([x, y], false);
+            }
+        }
+
+        self.set_promoted(fromc, false);
+        self.set_promoted(toc, promoted);
+    }
+
+    pub fn is_promoted(&self, loc: Loc) -> bool {
+        self.promoted[loc[1] as usize - 1][loc[0] as usize - 1]
+    }
+
+    pub fn set_promoted(&mut self, loc: Loc, promoted: bool) {
+        self.promoted[loc[1] as usize - 1][loc[0] as usize - 1] = promoted;
+    }
+
+    // the clocks, counters and turn after either kind of move, then checks if the game is over.
+    fn finish_move(&mut self, resets: bool) {
+
+        if let Some(clock) = &mut self.clock {
+            clock.press();
+        }
+        self.halfmove_clock = if resets {0} else {self.halfmove_clock + 1};
+        if !self.turn_white {
+            self.fullmove += 1;
+        }
+        self.turn_white = !self.turn_white;
+
+        if self.variant.in_check(&self.board, self.turn_white) {
+            self.checks[side(!self.turn_white)] += 1;
+        }
+
+        self.outcome = self.variant.outcome(self);
+    }
+
+    pub fn checks_given(&self, is_white: bool) -> u8 {
+        self.checks[side(is_white)]
+    }
+
+    // ends the game if the side to move has run out of time.
+    pub fn tick(&mut self) {
+        if self.outcome.is_some() {
+            return;
+        }
+
+        if let Some(clock) = &self.clock {
+            if clock.flagged(self.turn_white) {
+                self.outcome = Some(Outcome::Timeout(!self.turn_white));
+            }
+        }
+    }
+}
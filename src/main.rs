@@ -1,11 +1,186 @@
 
-#![feature(let_chains)]
 mod display;
 mod board;
+mod clock;
+mod fen;
+mod game;
 mod check;
 mod types;
 mod piece;
+mod betza;
+mod chess960;
+mod variant;
+mod pocket;
+mod bughouse;
+mod engine;
+mod umpire;
+mod net;
+mod uci;
+#[cfg(test)]
+mod test;
+
+const USAGE: &str = "usage: chess [--host <port> | --join <address>] [--variant <name> | --variant-file <path>] [--hash <MB>]
+                    [--engine <path> [--engine-option <name>=<value>]...] [--fen <FEN>]
+       chess bench [<depth>] [--hash <MB>] [--without <heuristic>]...
+       chess uci";
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    std::process::exit(2);
+}
+
+// searches the bench positions and says how it went, to measure changes to the search.
+fn bench(args: &[String]) -> ! {
+    let mut depth = 5;
+    let mut mb = engine::table::Table::DEFAULT_MB;
+    let mut options = engine::search::Options::default();
+
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--hash" => match rest.next().map(|mb| mb.parse::<usize>()) {
+                Some(Ok(n)) if n > 0 => mb = n,
+                _ => usage()
+            },
+            "--without" => match rest.next() {
+                Some(name) if options.set(name, false) => (),
+                Some(_) => {
+                    eprintln!("there's nothing called that to turn off. try one of: {}", engine::search::Options::NAMES.join(", "));
+                    std::process::exit(2);
+                },
+                None => usage()
+            },
+            depth_arg => match depth_arg.parse::<u32>() {
+                Ok(n) if n > 0 => depth = n,
+                _ => usage()
+            }
+        }
+    }
+
+    let bench = engine::bench::bench(depth, mb, options);
+    for (i, (fen, search)) in engine::bench::POSITIONS.iter().zip(&bench.searches).enumerate() {
+        let game = fen::from_fen(fen).expect("the bench positions are all valid");
+        let play = search.play.map_or("none".to_string(), |play| play.name(&game));
+        println!("{:>2}  {:<6} score {:>6}  nodes {:>9}", i + 1, play, search.score, search.nodes);
+    }
+    println!("nodes {}  time {:.2}s  nps {}  hash hits {:.1}%",
+        bench.nodes, bench.time.as_secs_f64(), bench.nodes_per_second(), bench.stats.hit_rate() * 100.0);
+    std::process::exit(0);
+}
+
 fn main() -> Result<(), std::io::Error> {
-    display::dynamic::start_terminal()?;
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "bench") {
+        bench(&args[1..]);
+    }
+    // played by a GUI instead of in the terminal.
+    if args.first().is_some_and(|arg| arg == "uci") {
+        return uci::run();
+    }
+    let mut variant = None;
+    let mut fen = None;
+    let mut host = None;
+    let mut join = None;
+    let mut engine = None;
+    let mut engine_options = Vec::new();
+
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--variant" => match rest.next().map(|name| variant::find(name)) {
+                Some(Some(found)) => variant = Some(found),
+                Some(None) => {
+                    let names: Vec<&str> = variant::VARIANTS.iter().map(|v| v.name()).collect();
+                    eprintln!("there's no variant called that. try one of: {}", names.join(", "));
+                    std::process::exit(2);
+                },
+                None => usage()
+            },
+            "--variant-file" => match rest.next().map(|path| (path, variant::config::load(path))) {
+                Some((path, Ok(loaded))) => {
+                    display::dynamic::record_variant_file(loaded.name(), path);
+                    variant = Some(loaded);
+                },
+                Some((_, Err(e))) => {
+                    eprintln!("couldn't load that variant: {}", e);
+                    std::process::exit(2);
+                },
+                None => usage()
+            },
+            // how much memory the computer gets for remembering positions.
+            "--hash" => match rest.next().map(|mb| mb.parse::<usize>()) {
+                Some(Ok(mb)) if mb > 0 => display::set_hash_size(mb),
+                _ => usage()
+            },
+            // another UCI engine to play against, or analyse with.
+            "--engine" => match rest.next() {
+                Some(path) => engine = Some(path.clone()),
+                None => usage()
+            },
+            "--engine-option" => match rest.next().and_then(|option| option.split_once('=')) {
+                Some((name, value)) => engine_options.push((name.to_string(), value.to_string())),
+                None => usage()
+            },
+            "--host" => match rest.next().map(|port| port.parse::<u16>()) {
+                Some(Ok(port)) => host = Some(port),
+                _ => usage()
+            },
+            "--join" => match rest.next() {
+                Some(address) => join = Some(address.clone()),
+                None => usage()
+            },
+            // a FEN has spaces in it, so it takes up the rest of the line.
+            "--fen" => {
+                fen = Some(rest.by_ref().cloned().collect::<Vec<String>>().join(" "));
+            },
+            _ => usage()
+        }
+    }
+
+    match engine {
+        Some(path) => display::set_engine(path, engine_options),
+        None if !engine_options.is_empty() => usage(),
+        None => ()
+    }
+
+    // a position to start from, e.g. one taken from a crash report.
+    let start = match (fen, variant) {
+        (Some(fen), variant) => match variant.map_or_else(|| fen::from_fen(&fen), |variant| fen::from_variant_fen(variant, &fen)) {
+            Ok(game) => Some(game),
+            Err(e) => {
+                eprintln!("couldn't read that position: {}", e);
+                std::process::exit(2);
+            }
+        },
+        (None, Some(variant)) => Some(game::Game::new(variant, variant.start().unwrap_or(board::STANDARD_BOARD), None)),
+        (None, None) => None
+    };
+
+    // a game against someone else, who either joins this one or has one ready to join.
+    let (start, peer) = match (host, join) {
+        (Some(port), None) => {
+            let game = start.unwrap_or_else(|| game::Game::new(&variant::Standard, board::STANDARD_BOARD, None));
+            eprintln!("waiting for someone to join on port {}...", port);
+            match net::host(port, &game) {
+                Ok(peer) => (Some(game), Some(peer)),
+                Err(e) => {
+                    eprintln!("couldn't host a game: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        },
+        (None, Some(address)) => match net::join(&address) {
+            Ok((game, peer)) => (Some(game), Some(peer)),
+            Err(e) => {
+                eprintln!("couldn't join that game: {}", e);
+                std::process::exit(1);
+            }
+        },
+        (None, None) => (start, None),
+        (Some(_), Some(_)) => usage()
+    };
+
+    display::dynamic::start_terminal(start, peer)?;
     Ok(())
 }
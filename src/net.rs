@@ -0,0 +1,138 @@
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream}
+};
+use crate::{
+    board::Loc,
+    fen::{from_variant_fen, parse_square, square_name, to_fen},
+    game::Game,
+    pocket,
+    types::Promotion,
+    variant
+};
+
+
+// games against someone on another computer, over a plain TCP connection.
+// both sides run the rules for themselves and just tell each other what they played, a line at a time.
+// that means in variants that hide pieces each side still has the whole position, and only the screen keeps it from the player.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Message {
+    // the piece a pawn became, if the move took one to the far end.
+    Move(Loc, Loc, Option<Promotion>),
+    Drop(usize, Loc),
+    // a move that was tried and refused, in variants with an umpire, who tells both players so.
+    Illegal
+}
+
+// the square at the start of 'line', a file letter and then the rank's digits, and whatever follows it.
+fn split_square(height: isize, line: &str) -> Option<(Loc, &str)> {
+    let end = line.char_indices().skip(1).find(|(_, c)| !c.is_ascii_digit()).map_or(line.len(), |(i, _)| i);
+    Some((parse_square(height, line.get(..end)?)?, &line[end..]))
+}
+
+impl Message {
+    // moves are written the way UCI writes them, like "e7e8q", drops like "N@f3", and refused tries as "illegal".
+    // the ranks are numbered from the bottom of a board 'height' ranks tall.
+    pub fn to_line(self, height: isize) -> String {
+        match self {
+            Message::Move(fromc, toc, promotion) => format!("{}{}{}", square_name(height, fromc), square_name(height, toc),
+                promotion.map_or(String::new(), |promotion| promotion.letter().to_ascii_lowercase().to_string())),
+            Message::Drop(kind, toc) => format!("{}@{}", pocket::KINDS[kind], square_name(height, toc)),
+            Message::Illegal => "illegal".to_string()
+        }
+    }
+
+    pub fn from_line(line: &str, height: isize) -> Option<Message> {
+        let line = line.trim();
+        if line == "illegal" {
+            return Some(Message::Illegal);
+        }
+        if let Some((kind, square)) = line.split_once('@') {
+            let kind = pocket::KINDS.iter().position(|letter| kind == letter.to_string())?;
+            return Some(Message::Drop(kind, parse_square(height, square)?));
+        }
+
+        let (fromc, rest) = split_square(height, line)?;
+        let (toc, rest) = split_square(height, rest)?;
+        let mut rest = rest.chars();
+        let promotion = match (rest.next(), rest.next()) {
+            (None, _) => None,
+            (Some(letter), None) => Some(Promotion::from_letter(letter)?),
+            _ => {return None;}
+        };
+        Some(Message::Move(fromc, toc, promotion))
+    }
+}
+
+// what came in from the other player.
+pub enum Incoming {
+    Message(Message),
+    Garbled(String),
+    Left
+}
+
+pub struct Peer {
+    stream: TcpStream,
+    // taken by whatever listens for the other player's moves.
+    reader: Option<BufReader<TcpStream>>,
+    // the side the other player has.
+    pub is_white: bool,
+    // how many ranks the board has, which the square names count from.
+    height: isize
+}
+
+impl Peer {
+    fn new(stream: TcpStream, reader: BufReader<TcpStream>, is_white: bool, height: isize) -> Peer {
+        Peer { stream, reader: Some(reader), is_white, height }
+    }
+
+    // if this doesn't get through, the connection is gone and the listener will say so.
+    pub fn send(&mut self, message: Message) {
+        let _ = writeln!(self.stream, "{}", message.to_line(self.height));
+    }
+
+    // everything the other player sends from here on, ending when they leave.
+    pub fn incoming(&mut self) -> Option<impl Iterator<Item = Incoming> + Send + 'static> {
+        let lines = self.reader.take()?.lines().map_while(Result::ok);
+        let height = self.height;
+        Some(lines.map(move |line| match Message::from_line(&line, height) {
+            Some(message) => Incoming::Message(message),
+            None => Incoming::Garbled(line)
+        }).chain(std::iter::once(Incoming::Left)))
+    }
+}
+
+fn garbled(what: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, what.to_string())
+}
+
+// waits for someone to join on 'port', then sends them the game to play. whoever hosts plays white.
+pub fn host(port: u16, game: &Game) -> io::Result<Peer> {
+    let listener = TcpListener::bind(("0.0.0.0", port))?;
+    let (mut stream, _) = listener.accept()?;
+
+    writeln!(stream, "variant {}", game.variant.name())?;
+    writeln!(stream, "fen {}", to_fen(game))?;
+
+    let reader = BufReader::new(stream.try_clone()?);
+    Ok(Peer::new(stream, reader, false, game.board.height))
+}
+
+// joins a game someone is hosting at 'address', and plays black in it.
+pub fn join(address: &str) -> io::Result<(Game, Peer)> {
+    let stream = TcpStream::connect(address)?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut read_field = |name: &str| -> io::Result<String> {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        line.trim_end().strip_prefix(name).map(|value| value.trim().to_string())
+            .ok_or_else(|| garbled("that isn't a chess game"))
+    };
+    let variant = variant::find(&read_field("variant")?).ok_or_else(|| garbled("the host is playing a variant this doesn't know"))?;
+    let game = from_variant_fen(variant, &read_field("fen")?).map_err(|e| garbled(&e.to_string()))?;
+
+    let height = game.board.height;
+    Ok((game, Peer::new(stream, reader, true, height)))
+}
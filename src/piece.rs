@@ -1,7 +1,8 @@
-use crate::board::{read_board, do_move, write_board};
+use crate::betza;
+use crate::board::{read_board, do_move};
 use crate::check::is_check;
 
-use super::types::{Direction, Direction::*, MoveData, Space, Space::*, Movement, Movement::*, PawnState };
+use super::types::{Direction, Direction::*, IllegalMove, MoveData, Space, Space::*, Movement, Movement::*, PawnState };
 use super::board::{Board, Loc};
 
 
@@ -29,7 +30,7 @@ fn get_relation(board: &Board, fromc: Loc, toc: Loc) -> Movement {
 
 fn test_line(board: &Board, fromc: Loc, dir: Direction, vector: &mut Vec<MoveData>) {
 
-    for index in 1..9 {
+    for index in 1.. {
 
         let toc = dir.translate(fromc, index);
         let relation = get_relation(board, fromc, toc);
@@ -42,7 +43,7 @@ fn test_line(board: &Board, fromc: Loc, dir: Direction, vector: &mut Vec<MoveDat
                 vector.push(MoveData {relation, to: toc});
                 break;
             },
-            PawnSkip | Blocked | QueenSide | KingSide | EnPessant => {
+            PawnSkip | Blocked | QueenSide | KingSide | EnPessant | Drop => {
                 break;
             }
         }
@@ -63,10 +64,13 @@ fn pawn_list(board: &Board, fromc: Loc, from: Space, vector: &mut Vec<MoveData>)
 
     if relation == Empty {
         vector.push(MoveData {relation, to: slide});
-        let slide = dir.translate(fromc, 2);
-        let relation = get_relation(board, fromc, slide);
-        if relation == Empty {
-            vector.push(MoveData {relation, to: slide});
+
+        // only an unmoved pawn may skip a space.
+        if let Pawn(_, PawnState::NotMoved) = from {
+            let skip = dir.translate(fromc, 2);
+            if get_relation(board, fromc, skip) == Empty {
+                vector.push(MoveData {relation: PawnSkip, to: skip});
+            }
         }
     }
 
@@ -93,7 +97,7 @@ fn pawn_list(board: &Board, fromc: Loc, from: Space, vector: &mut Vec<MoveData>)
         vector.push( MoveData {relation, to: attack} );
     } else if relation == Empty {
 
-        let pessantc = East.translate(fromc, 1);
+        let pessantc = West.translate(fromc, 1);
         let pessant = read_board(board, pessantc);
 
         if let Some(Pawn(w, PawnState::PrevSkipped)) = pessant {
@@ -104,14 +108,14 @@ fn pawn_list(board: &Board, fromc: Loc, from: Space, vector: &mut Vec<MoveData>)
     }
 }
 
-fn rook_list(board: &Board, fromc: Loc, from: Space, vector: &mut Vec<MoveData>) {
+fn rook_list(board: &Board, fromc: Loc, vector: &mut Vec<MoveData>) {
     for dir in Direction::CARDINALS {
         test_line(board, fromc, dir, vector);
     }
 }
 
 pub const KNIGHT_MOVES: [Loc; 8] = [[1,2],[2,1],[-1,2],[-2,1],[1,-2],[2,-1],[-1,-2],[-2,-1]];
-fn knight_list(board: &Board, fromc: Loc, from: Space, vector: &mut Vec<MoveData>) {
+fn knight_list(board: &Board, fromc: Loc, vector: &mut Vec<MoveData>) {
 
     for delta in KNIGHT_MOVES {
 
@@ -124,13 +128,13 @@ fn knight_list(board: &Board, fromc: Loc, from: Space, vector: &mut Vec<MoveData
     }
 }
 
-fn bishop_list(board: &Board, fromc: Loc, from: Space, vector: &mut Vec<MoveData>) {
+fn bishop_list(board: &Board, fromc: Loc, vector: &mut Vec<MoveData>) {
     for dir in Direction::ORDINALS {
         test_line(board, fromc, dir, vector);
     }
 }
 
-fn queen_list(board: &Board, fromc: Loc, from: Space, vector: &mut Vec<MoveData>) {
+fn queen_list(board: &Board, fromc: Loc, vector: &mut Vec<MoveData>) {
     for dir in Direction::ORDINALS {
         test_line(board, fromc, dir, vector);
     }
@@ -139,14 +143,25 @@ fn queen_list(board: &Board, fromc: Loc, from: Space, vector: &mut Vec<MoveData>
     }
 }
 
+// the archbishop moves as a bishop or a knight, and the chancellor as a rook or a knight.
+fn archbishop_list(board: &Board, fromc: Loc, vector: &mut Vec<MoveData>) {
+    bishop_list(board, fromc, vector);
+    knight_list(board, fromc, vector);
+}
+
+fn chancellor_list(board: &Board, fromc: Loc, vector: &mut Vec<MoveData>) {
+    rook_list(board, fromc, vector);
+    knight_list(board, fromc, vector);
+}
+
 fn king_list(board: &Board, fromc: Loc, from: Space, vector: &mut Vec<MoveData>) {
 
-    let (is_white, has_moved) = match from {
-        King(w, m) => (w, m),
+    let is_white = match from {
+        King(w, _) => w,
         _ => {return;}
     };
 
-    for dir in Direction::CARDINALS {
+    for dir in Direction::CARDINALS.into_iter().chain(Direction::ORDINALS) {
 
         let toc = dir.translate(fromc, 1);
         let to = read_board(board, toc);
@@ -155,59 +170,103 @@ fn king_list(board: &Board, fromc: Loc, from: Space, vector: &mut Vec<MoveData>)
             Some(Open) => {
                 vector.push(MoveData { relation: Empty, to: toc });
             },
-            Some(piece) => {
-                if piece.is_white() != is_white {
-                    vector.push(MoveData { relation: Enemy, to: toc });
-                }
+            Some(piece) if piece.is_white() != is_white => {
+                vector.push(MoveData { relation: Enemy, to: toc });
             },
-            None => {}
+            Some(_) | None => {}
         }
     }
 
     // castle rules :)
-    // cant castle if the king has moved.
-    if has_moved {return;}
-
-    //cant castle if king is in check
-    if is_check(board, fromc, is_white) {return;}
-
-    castle_check(board, fromc, from, East, vector);
-    castle_check(board, fromc, from, West, vector);
+    for dir in [East, West] {
+        if let Ok(castle) = castle_check(board, fromc, from, dir) {
+            vector.push(castle);
+        }
+    }
 }
 
-fn castle_check(board: &Board, fromc: Loc, from: Space, dir: Direction, vector: &mut Vec<MoveData>) {
-    let rook = read_board(board, dir.translate(fromc,
-        if dir == East {
-            3
-        } else {
-            4
+// the rook the king at 'kingc' would castle with on the 'dir' side, if it still can.
+// in Chess960 the rooks can start anywhere along the back rank, so this looks along it.
+pub fn castle_rook(board: &Board, kingc: Loc, is_white: bool, dir: Direction) -> Option<Loc> {
+    for n in 1.. {
+        let testc = dir.translate(kingc, n);
+        match read_board(board, testc) {
+            Some(Rook(w, false)) if w == is_white => {return Some(testc);},
+            Some(_) => {},
+            None => {return None;}
         }
-    ));
+    }
+    None
+}
 
-    if let Some(Rook(_, false)) = rook {
-    } else {
-        return;
+// where the king and rook end up after castling towards 'dir'.
+// these are the usual squares no matter where the pieces started,
+// counted from the far edge on the king's side so wider boards castle to the i and h files.
+pub fn castle_targets(board: &Board, kingc: Loc, dir: Direction) -> (Loc, Loc) {
+    match dir {
+        East => ([board.width - 1, kingc[1]], [board.width - 2, kingc[1]]),
+        _ => ([3, kingc[1]], [4, kingc[1]])
     }
+}
 
-    for n in [1, 2] {
+// works out if the king at 'fromc' can castle towards 'dir',
+// and if it can't, why not.
+pub fn castle_check(board: &Board, fromc: Loc, from: Space, dir: Direction) -> Result<MoveData, IllegalMove> {
 
-        let toc = dir.translate(fromc, n);
+    // cant castle if the king has moved.
+    let is_white = match from {
+        King(w, false) => w,
+        King(_, true) => {return Err(IllegalMove::CastlingRightsLost);},
+        _ => {return Err(IllegalMove::NotReachable);}
+    };
 
-        if !(read_board(board, toc) == Some(Open)) {
-            return;
+    let relation = match dir {
+        East => KingSide,
+        West => QueenSide,
+        _ => {return Err(IllegalMove::NotReachable);}
+    };
+
+    // nor if the rook has.
+    let rookc = match castle_rook(board, fromc, is_white, dir) {
+        Some(rookc) => rookc,
+        None => {return Err(IllegalMove::CastlingRightsLost);}
+    };
+
+    // everything the king and rook pass over or land on has to be empty, apart from themselves.
+    let (king_to, rook_to) = castle_targets(board, fromc, dir);
+    let xs = [fromc[0], rookc[0], king_to[0], rook_to[0]];
+    for x in *xs.iter().min().unwrap()..=*xs.iter().max().unwrap() {
+        let testc = [x, fromc[1]];
+        if testc != fromc && testc != rookc && read_board(board, testc) != Some(Open) {
+            return Err(IllegalMove::NotReachable);
         }
+    }
+
+    //cant castle if king is in check, or if it passes through check
+    if is_check(board, fromc, is_white) {
+        return Err(IllegalMove::CastlingThroughCheck);
+    }
+
+    let step = if king_to[0] > fromc[0] {East} else {West};
+    let mut testc = fromc;
+    while testc != king_to {
+        testc = step.translate(testc, 1);
 
-        let mut test_board = board.clone();
-        do_move(&mut test_board, fromc, from, toc, Empty);
-        if is_check(&test_board, fromc, from.is_white()) {
-            return;
+        let mut test_board = *board;
+        do_move(&mut test_board, fromc, from, testc, Empty);
+        if is_check(&test_board, testc, is_white) {
+            return Err(IllegalMove::CastlingThroughCheck);
         }
     }
-    vector.push(MoveData { relation: match dir {
-        East => KingSide,
-        West => QueenSide,
-        _ => {return;}
-    }, to: dir.translate(fromc, 2) });
+
+    // the rook moving away can uncover the square the king lands on.
+    let mut test_board = *board;
+    do_move(&mut test_board, fromc, from, king_to, relation);
+    if is_check(&test_board, king_to, is_white) {
+        return Err(IllegalMove::CastlingThroughCheck);
+    }
+
+    Ok(MoveData { relation, to: king_to })
 }
 
 pub fn move_list(board: &Board, fromc: Loc, from: Space) -> Vec<MoveData> {
@@ -219,20 +278,29 @@ pub fn move_list(board: &Board, fromc: Loc, from: Space) -> Vec<MoveData> {
             pawn_list(board, fromc, from, &mut vector);
         },
         Rook(_, _) => {
-            rook_list(board, fromc, from, &mut vector);
+            rook_list(board, fromc, &mut vector);
         },
         Knight(_) => {
-            knight_list(board, fromc, from, &mut vector);
+            knight_list(board, fromc, &mut vector);
         },
         Bishop(_) => {
-            bishop_list(board, fromc, from, &mut vector);
+            bishop_list(board, fromc, &mut vector);
         },
         Queen(_) => {
-            queen_list(board, fromc, from, &mut vector);
+            queen_list(board, fromc, &mut vector);
         },
         King(_, _) => {
             king_list(board, fromc, from, &mut vector);
         },
+        Archbishop(_) => {
+            archbishop_list(board, fromc, &mut vector);
+        },
+        Chancellor(_) => {
+            chancellor_list(board, fromc, &mut vector);
+        },
+        Fairy(_, _, _) => {
+            betza::move_list(board, fromc, from, &mut vector);
+        },
         Open => {}
     }
     vector
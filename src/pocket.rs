@@ -0,0 +1,81 @@
+use crate::{
+    board::{Board, Loc, do_move, read_board},
+    check::in_check,
+    types::{IllegalMove, Movement::Drop, PawnState, Space, Space::*}
+};
+
+
+// the pieces a side is holding in Crazyhouse, counted by kind: pawns, knights, bishops, rooks, queens.
+pub type Pocket = [u8; 5];
+
+pub const KINDS: [char; 5] = ['P', 'N', 'B', 'R', 'Q'];
+pub const NAMES: [&str; 5] = ["pawn", "knight", "bishop", "rook", "queen"];
+
+// which slot of a pocket a piece goes in; kings never do.
+pub fn kind(piece: Space) -> Option<usize> {
+    match piece {
+        Pawn(_, _) => Some(0),
+        Knight(_) => Some(1),
+        Bishop(_) => Some(2),
+        Rook(_, _) => Some(3),
+        Queen(_) => Some(4),
+        King(_, _) | Archbishop(_) | Chancellor(_) | Fairy(_, _, _) | Open => None
+    }
+}
+
+// the piece that comes out of slot 'kind' when it's dropped on 'toc'.
+// a pawn dropped on its starting rank can still skip, but a dropped rook can't castle.
+pub fn piece(board: &Board, kind: usize, is_white: bool, toc: Loc) -> Space {
+    match kind {
+        0 => Pawn(is_white, match toc[1] == if is_white {board.height - 1} else {2} {
+            true => PawnState::NotMoved,
+            false => PawnState::Moved
+        }),
+        1 => Knight(is_white),
+        2 => Bishop(is_white),
+        3 => Rook(is_white, true),
+        _ => Queen(is_white)
+    }
+}
+
+// works out if a piece of 'kind' can be dropped on 'toc', and if it can't, why not.
+pub fn drop_check(board: &Board, pocket: &Pocket, kind: usize, is_white: bool, toc: Loc) -> Result<(), IllegalMove> {
+
+    if pocket[kind] == 0 {
+        return Err(IllegalMove::NothingToDrop);
+    }
+
+    if read_board(board, toc) != Some(Open) {
+        return Err(IllegalMove::DropOnPiece);
+    }
+
+    if kind == 0 && (toc[1] == 1 || toc[1] == board.height) {
+        return Err(IllegalMove::PawnDropRank);
+    }
+
+    // only a drop that blocks the check is allowed while in check.
+    let mut test_board = *board;
+    do_move(&mut test_board, toc, piece(board, kind, is_white, toc), toc, Drop);
+    if in_check(&test_board, is_white) {
+        return Err(IllegalMove::KingInCheck);
+    }
+
+    Ok(())
+}
+
+// every square a piece of 'kind' could be dropped on.
+pub fn drop_squares(board: &Board, pocket: &Pocket, kind: usize, is_white: bool) -> Vec<Loc> {
+    let mut squares = Vec::new();
+    for y in 1..=board.height {
+        for x in 1..=board.width {
+            if drop_check(board, pocket, kind, is_white, [x, y]).is_ok() {
+                squares.push([x, y]);
+            }
+        }
+    }
+    squares
+}
+
+pub fn can_drop(board: &Board, pocket: &Pocket, is_white: bool) -> bool {
+    (0..KINDS.len()).any(|kind| !drop_squares(board, pocket, kind, is_white).is_empty())
+}
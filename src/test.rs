@@ -1,5 +1,1142 @@
+use crate::betza::{self, BetzaError, FairyPiece};
+use crate::board::{Board, Loc, move_piece, read_board, write_board};
+use crate::check::in_check;
+use crate::bughouse::settle;
+use crate::chess960;
+use crate::engine::{bench, order, plays, search, see, Play, eval::{self, Score}, search::{MATE, Options}, table::{self, Bound, Table}, time::{Limits, TimeManager}};
+use crate::fen::{from_fen, from_variant_fen, to_fen, FenError};
+use crate::game::{Game, Outcome};
+use crate::net::Message;
+use crate::types::{IllegalMove, PawnState, Promotion, Space, Space::*};
+use crate::uci::{self, Engine, Out, client::{self, Client, ClientError}};
+use crate::umpire::{announce, Announcement, CheckLine};
+use crate::variant::config::{self, ConfigError};
+use crate::variant::{Antichess, Atomic, Bughouse, Capablanca, Crazyhouse, FogOfWar, Grand, Horde, KingOfTheHill, Kriegspiel, RacingKings, Standard, ThreeCheck, Variant};
 
-#[cfg(test)]
-mod test {
+// turns a square name like "e4" into an in game location.
+fn sq(name: &str) -> Loc {
+    let bytes = name.as_bytes();
+    [(bytes[0] - b'a') as isize + 1, 9 - (bytes[1] - b'0') as isize]
+}
+
+fn board_from(pieces: &[(&str, Space)]) -> Board {
+    let mut board = Board::empty(8, 8);
+    for (name, piece) in pieces {
+        write_board(&mut board, sq(name), *piece);
+    }
+    board
+}
+
+#[test]
+fn pawns_give_check() {
+    let board = board_from(&[("e4", King(true, true)), ("d5", Pawn(false, PawnState::Moved))]);
+    assert!(in_check(&board, true));
+
+    let board = board_from(&[("e4", King(true, true)), ("d3", Pawn(false, PawnState::Moved))]);
+    assert!(!in_check(&board, true));
+
+    let board = board_from(&[("e5", King(false, true)), ("f4", Pawn(true, PawnState::Moved))]);
+    assert!(in_check(&board, false));
+}
+
+#[test]
+fn kings_move_diagonally_and_keep_apart() {
+    let board = board_from(&[("e4", King(true, true)), ("e6", King(false, true))]);
+    assert!(!in_check(&board, true));
+    let board = board_from(&[("e4", King(true, true)), ("f5", King(false, true))]);
+    assert!(in_check(&board, true) && in_check(&board, false));
+
+    // a try at moving the white king, leaving the board as it was.
+    let step = |mut board: Board, to| move_piece(&Standard, &mut board, sq("e4"), sq(to), true, Promotion::Queen);
+    let mut board = board_from(&[("e4", King(true, true)), ("e6", King(false, true)), ("a8", Rook(false, true))]);
+    assert_eq!(step(board, "d5"), Err(IllegalMove::KingInCheck));
+    assert_eq!(step(board, "d3"), Ok(()));
+    // the square it's going to is the one that has to be safe, not the one it's leaving.
+    write_board(&mut board, sq("a8"), Open);
+    write_board(&mut board, sq("a3"), Rook(false, true));
+    assert_eq!(step(board, "f3"), Err(IllegalMove::KingInCheck));
+    assert_eq!(step(board, "f4"), Ok(()));
+}
+
+#[test]
+fn explains_basic_mistakes() {
+    let mut board = crate::board::STANDARD_BOARD;
+    assert_eq!(move_piece(&Standard, &mut board, sq("e4"), sq("e5"), true, Promotion::Queen), Err(IllegalMove::NoPiece));
+    assert_eq!(move_piece(&Standard, &mut board, sq("e7"), sq("e5"), true, Promotion::Queen), Err(IllegalMove::WrongSide));
+    assert_eq!(move_piece(&Standard, &mut board, sq("e2"), sq("e5"), true, Promotion::Queen), Err(IllegalMove::NotReachable));
+    assert_eq!(move_piece(&Standard, &mut board, sq("e2"), sq("e4"), true, Promotion::Queen), Ok(()));
+    assert_eq!(move_piece(&Standard, &mut board, sq("e4"), sq("e6"), false, Promotion::Queen), Err(IllegalMove::WrongSide));
+}
+
+#[test]
+fn explains_pins_and_checks() {
+    let pieces = [
+        ("e1", King(true, true)),
+        ("e2", Bishop(true)),
+        ("a2", Pawn(true, PawnState::Moved)),
+        ("e8", Rook(false, true)),
+        ("a8", King(false, true))
+    ];
+
+    let mut board = board_from(&pieces);
+    assert_eq!(move_piece(&Standard, &mut board, sq("e2"), sq("d3"), true, Promotion::Queen), Err(IllegalMove::Pinned));
+    assert_eq!(move_piece(&Standard, &mut board, sq("e1"), sq("d1"), true, Promotion::Queen), Ok(()));
+
+    let mut board = board_from(&pieces[1..]);
+    write_board(&mut board, sq("e1"), Open);
+    write_board(&mut board, sq("d1"), King(true, true));
+    write_board(&mut board, sq("e2"), Open);
+    write_board(&mut board, sq("d8"), Rook(false, true));
+    assert_eq!(move_piece(&Standard, &mut board, sq("a2"), sq("a3"), true, Promotion::Queen), Err(IllegalMove::KingInCheck));
+    assert_eq!(move_piece(&Standard, &mut board, sq("d1"), sq("d2"), true, Promotion::Queen), Err(IllegalMove::KingInCheck));
+    assert_eq!(move_piece(&Standard, &mut board, sq("d1"), sq("c2"), true, Promotion::Queen), Ok(()));
+}
+
+#[test]
+fn explains_castling() {
+    let pieces = [
+        ("e1", King(true, false)),
+        ("h1", Rook(true, false)),
+        ("a1", Rook(true, true)),
+        ("f8", Rook(false, true)),
+        ("a8", King(false, true))
+    ];
+
+    let mut board = board_from(&pieces);
+    assert_eq!(move_piece(&Standard, &mut board, sq("e1"), sq("g1"), true, Promotion::Queen), Err(IllegalMove::CastlingThroughCheck));
+    assert_eq!(move_piece(&Standard, &mut board, sq("e1"), sq("c1"), true, Promotion::Queen), Err(IllegalMove::CastlingRightsLost));
+
+    let mut board = board_from(&pieces[..3]);
+    write_board(&mut board, sq("a8"), King(false, true));
+    assert_eq!(move_piece(&Standard, &mut board, sq("e1"), sq("g1"), true, Promotion::Queen), Ok(()));
+    assert_eq!(board.squares[7][5], Rook(true, true));
+    assert_eq!(board.squares[7][6], King(true, true));
+}
+
+#[test]
+fn en_pessant_only_right_after_the_skip() {
+    let mut board = board_from(&[
+        ("e1", King(true, true)),
+        ("e5", Pawn(true, PawnState::Moved)),
+        ("h2", Pawn(true, PawnState::NotMoved)),
+        ("e8", King(false, true)),
+        ("d7", Pawn(false, PawnState::NotMoved)),
+        ("a7", Pawn(false, PawnState::NotMoved))
+    ]);
+
+    let mut later = board;
+    assert_eq!(move_piece(&Standard, &mut board, sq("d7"), sq("d5"), false, Promotion::Queen), Ok(()));
+    assert_eq!(move_piece(&Standard, &mut board, sq("e5"), sq("d6"), true, Promotion::Queen), Ok(()));
+    assert_eq!(board.squares[4][3], Open);
+
+    assert_eq!(move_piece(&Standard, &mut later, sq("d7"), sq("d5"), false, Promotion::Queen), Ok(()));
+    assert_eq!(move_piece(&Standard, &mut later, sq("h2"), sq("h3"), true, Promotion::Queen), Ok(()));
+    assert_eq!(move_piece(&Standard, &mut later, sq("a7"), sq("a6"), false, Promotion::Queen), Ok(()));
+    assert_eq!(move_piece(&Standard, &mut later, sq("e5"), sq("d6"), true, Promotion::Queen), Err(IllegalMove::NotReachable));
+}
+
+#[test]
+fn en_pessant_either_side() {
+    let start = board_from(&[
+        ("e1", King(true, true)),
+        ("e5", Pawn(true, PawnState::Moved)),
+        ("e8", King(false, true)),
+        ("d7", Pawn(false, PawnState::NotMoved)),
+        ("f7", Pawn(false, PawnState::NotMoved))
+    ]);
+
+    // a skip past the east side of the pawn, then the west, and only the pawn that skipped can be taken.
+    for (skip, landing, to, other) in [("f7", "f5", "f6", "d6"), ("d7", "d5", "d6", "f6")] {
+        let mut board = start;
+        assert_eq!(move_piece(&Standard, &mut board, sq(skip), sq(landing), false, Promotion::Queen), Ok(()));
+        let mut wrong = board;
+        assert_eq!(move_piece(&Standard, &mut wrong, sq("e5"), sq(other), true, Promotion::Queen), Err(IllegalMove::NotReachable));
+        assert_eq!(move_piece(&Standard, &mut board, sq("e5"), sq(to), true, Promotion::Queen), Ok(()));
+        assert_eq!(crate::board::read_board(&board, sq(landing)), Some(Open));
+    }
+}
+
+#[test]
+fn pawns_skip_only_from_the_start() {
+    let mut board = board_from(&[
+        ("e1", King(true, true)),
+        ("a2", Pawn(true, PawnState::NotMoved)),
+        ("b3", Pawn(true, PawnState::Moved)),
+        ("c2", Pawn(true, PawnState::NotMoved)),
+        ("c4", Knight(false)),
+        ("e8", King(false, true))
+    ]);
+    assert_eq!(move_piece(&Standard, &mut board, sq("b3"), sq("b5"), true, Promotion::Queen), Err(IllegalMove::NotReachable));
+    assert_eq!(move_piece(&Standard, &mut board, sq("c2"), sq("c4"), true, Promotion::Queen), Err(IllegalMove::NotReachable));
+    assert_eq!(move_piece(&Standard, &mut board, sq("a2"), sq("a4"), true, Promotion::Queen), Ok(()));
+    assert_eq!(crate::board::read_board(&board, sq("a4")), Some(Pawn(true, PawnState::PrevSkipped)));
+    assert_eq!(move_piece(&Standard, &mut board, sq("e8"), sq("d8"), false, Promotion::Queen), Ok(()));
+    assert_eq!(move_piece(&Standard, &mut board, sq("a4"), sq("a6"), true, Promotion::Queen), Err(IllegalMove::NotReachable));
+}
+
+#[test]
+fn fen_follows_the_game() {
+    let mut game = Game::new(&Standard, crate::board::STANDARD_BOARD, None);
+    assert_eq!(to_fen(&game), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+
+    game.play(sq("e2"), sq("e4")).unwrap();
+    assert_eq!(to_fen(&game), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+
+    game.play(sq("g8"), sq("f6")).unwrap();
+    game.play(sq("e1"), sq("e2")).unwrap();
+    assert_eq!(to_fen(&game), "rnbqkb1r/pppppppp/5n2/8/4P3/8/PPPPKPPP/RNBQ1BNR b kq - 2 2");
+}
+
+#[test]
+fn fen_round_trips() {
+    for fen in [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "4k2r/8/8/8/8/8/8/R3K3 b Qk - 7 40"
+    ] {
+        assert_eq!(to_fen(&from_fen(fen).unwrap()), fen);
+    }
+
+    // the en passant capture is really there.
+    let mut game = from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3").unwrap();
+    assert_eq!(game.play(sq("e5"), sq("f6")), Ok(()));
+}
+
+#[test]
+fn fen_errors() {
+    assert_eq!(from_fen("8/8/8 w").err(), Some(FenError::Placement));
+    assert_eq!(from_fen("8/8/8/8/8/8/8/8 w - - 0 1").err(), Some(FenError::Placement));
+    assert_eq!(from_fen("4k3/8/8/8/8/8/8/4K3 x").err(), Some(FenError::Turn));
+    assert_eq!(from_fen("4k3/8/8/8/8/8/8/4K3 w K - 0 1").err(), Some(FenError::Castling));
+    assert_eq!(from_fen("4k3/8/8/8/8/8/8/4K3 w - e6 0 1").err(), Some(FenError::EnPessant));
+    assert_eq!(from_fen("4k3/8/8/8/8/8/8/4K3 w - - x 1").err(), Some(FenError::Counter));
+}
+
+#[test]
+fn chess960_positions() {
+    assert_eq!(chess960::board(518), crate::board::STANDARD_BOARD);
+    assert_eq!(chess960::back_rank(0, true), [Bishop(true), Bishop(true), Queen(true), Knight(true), Knight(true), Rook(true, false), King(true, false), Rook(true, false)]);
+
+    let mut seen = Vec::new();
+    for number in 0..chess960::POSITIONS {
+        let rank = chess960::back_rank(number, true);
+        let find = |piece: Space| (0..8).filter(|x| rank[*x] == piece).collect::<Vec<usize>>();
+
+        let bishops = find(Bishop(true));
+        let rooks = find(Rook(true, false));
+        let king = find(King(true, false))[0];
+        assert!(bishops[0] % 2 != bishops[1] % 2);
+        assert!(rooks[0] < king && king < rooks[1]);
+        assert_eq!(find(Knight(true)).len(), 2);
+
+        assert!(!seen.contains(&rank));
+        seen.push(rank);
+    }
+}
+
+#[test]
+fn chess960_castling() {
+    // the king lands on c1 and the rook on d1 no matter where they started.
+    let mut game = from_fen("4k3/8/8/8/8/8/8/RK5R w AH - 0 1").unwrap();
+    assert_eq!(to_fen(&game), "4k3/8/8/8/8/8/8/RK5R w KQ - 0 1");
+    assert_eq!(game.play(sq("b1"), sq("a1")), Ok(()));
+    assert_eq!(to_fen(&game), "4k3/8/8/8/8/8/8/2KR3R b - - 1 1");
+
+    // here the king doesn't move at all, so only picking the rook says castle.
+    let mut game = from_fen("4k3/8/8/8/8/8/8/R5KR w KQ - 0 1").unwrap();
+    assert_eq!(game.play(sq("g1"), sq("h1")), Ok(()));
+    assert_eq!(to_fen(&game), "4k3/8/8/8/8/8/8/R4RK1 b - - 1 1");
+
+    // the g1 rook is in the way of the h1 one.
+    let mut game = from_fen("4k3/8/8/8/8/8/8/4K1RR w H - 0 1").unwrap();
+    assert_eq!(game.play(sq("e1"), sq("h1")), Err(IllegalMove::NotReachable));
+
+    // a rook with another further out is named by its file.
+    let game = from_fen("4k3/8/8/8/8/8/8/RK3R1R w FA - 0 1").unwrap();
+    assert_eq!(to_fen(&game), "4k3/8/8/8/8/8/8/RK3R1R w FQ - 0 1");
+}
+
+#[test]
+fn pawns_promote() {
+    let mut game = from_fen("8/4P1k1/8/8/8/8/1p6/4K3 w - - 0 1").unwrap();
+    game.play(sq("e7"), sq("e8")).unwrap();
+    game.play(sq("b2"), sq("b1")).unwrap();
+    assert_eq!(to_fen(&game), "4Q3/6k1/8/8/8/8/8/1q2K3 w - - 0 2");
+
+    let mut game = from_fen("8/4P1k1/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(game.play_promoting(sq("e7"), sq("e8"), Promotion::King), Err(IllegalMove::CantPromoteTo));
+    assert_eq!(game.play_promoting(sq("e7"), sq("e8"), Promotion::Knight), Ok(()));
+    assert_eq!(to_fen(&game), "4N3/6k1/8/8/8/8/8/4K3 b - - 0 1");
+}
+
+#[test]
+fn king_of_the_hill() {
+    let mut game = from_fen("4k3/8/8/8/8/4K3/8/8 w - - 0 1").unwrap();
+    game.variant = &KingOfTheHill;
+    game.play(sq("e3"), sq("e4")).unwrap();
+    assert_eq!(game.outcome, Some(Outcome::Hill(true)));
+
+    // it's only a normal move in standard chess.
+    let mut game = from_fen("4k3/8/8/8/8/4K3/8/8 w - - 0 1").unwrap();
+    game.play(sq("e3"), sq("e4")).unwrap();
+    assert_eq!(game.outcome, None);
+}
+
+#[test]
+fn three_check() {
+    let mut game = from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1 +2+0").unwrap();
+    game.variant = &ThreeCheck;
+    assert_eq!(game.checks_given(true), 2);
+    game.play(sq("a1"), sq("a8")).unwrap();
+    assert_eq!(game.outcome, Some(Outcome::ThirdCheck(true)));
+
+    // lichess counts the checks left to give instead, and puts them before the move counters.
+    let mut game = from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 3+1 0 1").unwrap();
+    game.variant = &ThreeCheck;
+    assert_eq!(game.checks, [0, 2]);
+    game.play(sq("a1"), sq("a8")).unwrap();
+    assert_eq!(game.outcome, None);
+    assert_eq!(to_fen(&game), "R3k3/8/8/8/8/8/8/4K3 b - - 1 1 +1+2");
+
+    assert_eq!(from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1 +4+0").err(), Some(FenError::Checks));
+}
+
+#[test]
+fn crazyhouse_pockets_and_drops() {
+    let mut game = from_fen("4k3/8/8/3p4/4N3/8/8/4K3[] w - - 0 1").unwrap();
+    game.variant = &Crazyhouse;
+    game.play(sq("e4"), sq("d6")).unwrap();
+    assert_eq!(to_fen(&game), "4k3/8/3N4/3p4/8/8/8/4K3[] b - - 1 1");
+
+    game.play(sq("e8"), sq("d7")).unwrap();
+    game.play(sq("d6"), sq("b7")).unwrap();
+    game.play(sq("d7"), sq("c7")).unwrap();
+    game.play(sq("b7"), sq("d8")).unwrap();
+    game.play(sq("c7"), sq("d8")).unwrap();
+    assert_eq!(game.pockets, [[0; 5], [0, 1, 0, 0, 0]]);
+
+    assert_eq!(game.drop(1, sq("e4")), Err(IllegalMove::NothingToDrop));
+    game.play(sq("e1"), sq("e2")).unwrap();
+    assert_eq!(game.drop(0, sq("e4")), Err(IllegalMove::NothingToDrop));
+    assert_eq!(game.drop(1, sq("d5")), Err(IllegalMove::DropOnPiece));
+    assert_eq!(game.drop(1, sq("f4")), Ok(()));
+    assert_eq!(to_fen(&game), "3k4/8/8/3p4/5n2/8/4K3/8[] w - - 2 5");
+}
+
+#[test]
+fn crazyhouse_rules() {
+    // pawns can't go on the back ranks, and a drop has to deal with check.
+    let mut game = from_fen("4k3/8/8/8/8/8/8/r3K3[P] w - - 0 1").unwrap();
+    game.variant = &Crazyhouse;
+    assert_eq!(game.drop(0, sq("c8")), Err(IllegalMove::PawnDropRank));
+    assert_eq!(game.drop(0, sq("c4")), Err(IllegalMove::KingInCheck));
+
+    // it isn't mate while a drop can block it.
+    let mut game = from_fen("4r2k/8/8/8/8/8/5PPP/6K1[N] b - - 0 1").unwrap();
+    game.variant = &Crazyhouse;
+    game.play(sq("e8"), sq("e1")).unwrap();
+    assert_eq!(game.outcome, None);
+    assert_eq!(game.drop(1, sq("e2")), Err(IllegalMove::KingInCheck));
+    assert_eq!(game.drop(1, sq("f1")), Ok(()));
+
+    let mut game = from_fen("4r2k/8/8/8/8/8/5PPP/6K1 b - - 0 1").unwrap();
+    game.play(sq("e8"), sq("e1")).unwrap();
+    assert_eq!(game.outcome, Some(Outcome::Checkmate(false)));
+
+    // promoted pieces go back to being pawns when taken.
+    let mut game = from_fen("r3k3/1P6/8/8/8/8/8/4K3[] w - - 0 1").unwrap();
+    game.variant = &Crazyhouse;
+    game.play(sq("b7"), sq("b8")).unwrap();
+    assert_eq!(to_fen(&game), "rQ~2k3/8/8/8/8/8/8/4K3[] b - - 0 1");
+    game.play(sq("a8"), sq("b8")).unwrap();
+    assert_eq!(game.pockets, [[0; 5], [1, 0, 0, 0, 0]]);
+    assert_eq!(to_fen(&from_fen("1Q~2k3/8/8/8/8/8/8/2r1K3 b - - 0 1").unwrap()), "1Q2k3/8/8/8/8/8/8/2r1K3 b - - 0 1");
+
+    // the pockets can come as a ninth rank instead, but only where there are pockets.
+    let game = from_variant_fen(&Crazyhouse, "1Q~2k3/8/8/8/8/8/8/2r1K3/Pn b - - 0 1").unwrap();
+    assert_eq!(to_fen(&game), "1Q~2k3/8/8/8/8/8/8/2r1K3[Pn] b - - 0 1");
+    let game = from_fen("4k3/8/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!((game.board.height, game.pockets), (9, [[0; 5]; 2]));
+}
+
+#[test]
+fn atomic_explosions() {
+    // the knight, the rook and the capturing queen all go, but the pawn stays.
+    let mut game = from_fen("4k3/8/2nrp3/3b4/8/8/8/3QK3 w - - 0 1").unwrap();
+    game.variant = &Atomic;
+    game.play(sq("d1"), sq("d5")).unwrap();
+    assert_eq!(to_fen(&game), "4k3/8/4p3/8/8/8/8/4K3 b - - 0 1");
+    assert_eq!(game.last_capture, Some(sq("d5")));
+
+    // taking next to the enemy king wins.
+    let mut game = from_fen("3qk3/8/8/8/8/8/8/3RK3 w - - 0 1").unwrap();
+    game.variant = &Atomic;
+    game.play(sq("d1"), sq("d8")).unwrap();
+    assert_eq!(game.outcome, Some(Outcome::Exploded(true)));
+}
+
+#[test]
+fn atomic_rules() {
+    // kings can't take, and nothing can blow up its own king.
+    let mut game = from_fen("8/8/8/8/8/3k4/3q4/4K3 w - - 0 1").unwrap();
+    game.variant = &Atomic;
+    assert_eq!(game.play(sq("e1"), sq("d2")), Err(IllegalMove::ExplodesOwnKing));
+
+    // kings that touch can't be in check, so the queen on h2 isn't giving one.
+    let game = from_fen("8/8/8/8/8/8/3kK2q/8 w - - 0 1").unwrap();
+    assert!(!Atomic.in_check(&game.board, true));
+    assert!(Standard.in_check(&game.board, true));
+
+    let mut game = from_fen("7k/8/8/8/8/8/r7/RK6 w - - 0 1").unwrap();
+    game.variant = &Atomic;
+    assert_eq!(game.play(sq("a1"), sq("a2")), Err(IllegalMove::ExplodesOwnKing));
+    assert_eq!(game.play(sq("b1"), sq("c1")), Ok(()));
+}
+
+#[test]
+fn antichess() {
+    // taking is compulsory, and kings can be taken like anything else.
+    let mut game = from_variant_fen(&Antichess, "8/8/8/3k4/4P3/8/8/R7 w - - 0 1").unwrap();
+    assert_eq!(game.play(sq("a1"), sq("a8")), Err(IllegalMove::MustCapture));
+    assert_eq!(game.play(sq("e4"), sq("d5")), Ok(()));
+    assert_eq!(to_fen(&game), "8/8/8/3P4/8/8/8/R7 b - - 0 1");
+    assert_eq!(game.outcome, Some(Outcome::OutOfPieces(false)));
+
+    // pawns can become kings, and there's no castling.
+    let mut game = from_variant_fen(&Antichess, "r3k3/1P6/8/8/8/8/8/4K2R w Kq - 0 1").unwrap();
+    assert_eq!(to_fen(&game), "r3k3/1P6/8/8/8/8/8/4K2R w - - 0 1");
+    assert_eq!(game.play(sq("e1"), sq("g1")), Err(IllegalMove::NotReachable));
+    assert_eq!(game.play_promoting(sq("b7"), sq("a8"), Promotion::King), Ok(()));
+    assert_eq!(to_fen(&game), "K3k3/8/8/8/8/8/8/4K2R b - - 0 1");
+
+    // being stuck wins too.
+    let mut game = from_variant_fen(&Antichess, "8/8/8/8/p7/8/P7/8 b - - 0 1").unwrap();
+    game.play(sq("a4"), sq("a3")).unwrap();
+    assert_eq!(game.outcome, Some(Outcome::OutOfMoves(true)));
+}
+
+#[test]
+fn horde() {
+    let game = from_variant_fen(&Horde, Horde::START).unwrap();
+    assert_eq!(to_fen(&game), Horde::START);
+    assert_eq!(from_variant_fen(&Horde, "4k3/8/8/8/8/8/8/4K3 w - - 0 1").err(), Some(FenError::Placement));
+
+    // pawns on the first rank can skip, and still can after stepping up to the second.
+    let mut game = from_variant_fen(&Horde, "4k3/8/8/8/8/8/8/P6P w - - 0 1").unwrap();
+    assert_eq!(game.play(sq("a1"), sq("a3")), Ok(()));
+    game.play(sq("e8"), sq("d8")).unwrap();
+    game.play(sq("h1"), sq("h2")).unwrap();
+    game.play(sq("d8"), sq("e8")).unwrap();
+    assert_eq!(game.play(sq("h2"), sq("h4")), Ok(()));
+
+    // only the skip from the second rank can be taken en passant.
+    let mut game = from_variant_fen(&Horde, "4k3/8/8/8/6p1/1p6/7P/P7 w - - 0 1").unwrap();
+    game.play(sq("a1"), sq("a3")).unwrap();
+    assert_eq!(to_fen(&game), "4k3/8/8/8/6p1/Pp6/7P/8 b - - 0 1");
+    assert_eq!(game.clone().play(sq("b3"), sq("a2")), Err(IllegalMove::NotReachable));
+    game.play(sq("e8"), sq("d8")).unwrap();
+    game.play(sq("h2"), sq("h4")).unwrap();
+    assert_eq!(to_fen(&game), "3k4/8/8/8/6pP/Pp6/8/8 b - h3 0 2");
+    assert_eq!(game.play(sq("g4"), sq("h3")), Ok(()));
+
+    // taking the last pawn wins for black.
+    let mut game = from_variant_fen(&Horde, "4k3/8/8/8/8/8/8/q6P b - - 0 1").unwrap();
+    game.play(sq("a1"), sq("h1")).unwrap();
+    assert_eq!(game.outcome, Some(Outcome::HordeTaken));
+}
+
+#[test]
+fn racing_kings() {
+    let game = from_variant_fen(&RacingKings, RacingKings::START).unwrap();
+    assert_eq!(to_fen(&game), RacingKings::START);
+
+    // nobody is allowed to give check.
+    let mut game = from_variant_fen(&RacingKings, "8/8/8/8/8/7K/k7/7R w - - 0 1").unwrap();
+    assert_eq!(game.play(sq("h1"), sq("h2")), Err(IllegalMove::GivesCheck));
+    assert_eq!(game.play(sq("h1"), sq("g1")), Ok(()));
+
+    // white gets there first and black can't catch up.
+    let mut game = from_variant_fen(&RacingKings, "8/4K3/k7/8/8/8/8/8 w - - 0 1").unwrap();
+    game.play(sq("e7"), sq("e8")).unwrap();
+    assert_eq!(game.outcome, Some(Outcome::KingHome(true)));
+
+    // but if black can, they get one more move to draw.
+    let mut game = from_variant_fen(&RacingKings, "8/k3K3/8/8/8/8/8/8 w - - 0 1").unwrap();
+    game.play(sq("e7"), sq("e8")).unwrap();
+    assert_eq!(game.outcome, None);
+    game.play(sq("a7"), sq("a8")).unwrap();
+    assert_eq!(game.outcome, Some(Outcome::BothKingsHome));
+}
+
+#[test]
+fn fog_of_war() {
+    // white sees its own pieces and where they can go, and nothing else.
+    let game = from_variant_fen(&FogOfWar, "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+    let seen = FogOfWar.visible(&game.board, true);
+    let is_seen = |name: &str| seen[sq(name)[1] as usize - 1][sq(name)[0] as usize - 1];
+    assert!(is_seen("e1") && is_seen("e2") && is_seen("e4") && is_seen("d1") && is_seen("f2"));
+    assert!(!is_seen("e8") && !is_seen("e5") && !is_seen("d3"));
+
+    // nobody warns the king, and taking it wins.
+    let mut game = from_variant_fen(&FogOfWar, "4k3/8/8/8/8/8/8/R3K3 b - - 0 1").unwrap();
+    assert_eq!(game.play(sq("e8"), sq("d8")), Ok(()));
+    assert!(!game.variant.in_check(&game.board, false));
+    game.play(sq("a1"), sq("a8")).unwrap();
+    assert_eq!(game.play(sq("d8"), sq("d7")), Ok(()));
+    game.play(sq("a8"), sq("a7")).unwrap();
+    game.play(sq("d7"), sq("c7")).unwrap();
+    game.play(sq("a7"), sq("c7")).unwrap();
+    assert_eq!(game.outcome, Some(Outcome::KingTaken(true)));
+}
+
+#[test]
+fn hidden_boards_face_the_person_playing() {
+    use crate::display::{app::Settings, events::UserState};
+    let game = from_variant_fen(&FogOfWar, "4k3/8/8/8/8/8/4P3/4K3 b - - 0 1").unwrap();
+
+    // two people at one screen see whoever's turn it is, and hand it over after each move.
+    let mut user = UserState::new(game.clone(), false, Settings::default());
+    assert!(!user.viewer() && user.hot_seat());
+
+    // against the computer it's always the person's side, even on the computer's turn, and there's nobody to hand it to.
+    user.computer = [false, true];
+    assert!(user.viewer() && !user.hot_seat());
+    let mut user = UserState::new(game, true, Settings::default());
+    user.computer = [true, false];
+    user.game.play(sq("e8"), sq("d8")).unwrap();
+    assert!(!user.viewer() && !user.hot_seat());
+}
+
+#[test]
+fn computer_drops_moves_from_an_old_game() {
+    use crate::display::{app::{App, Screen, Settings}, events::{UserState, computer}};
+    let game = from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+    let mut user = UserState::new(game.clone(), false, Settings::default());
+    user.computer = [true, false];
+    let (number, asked, limits, engine) = computer::wanted(&mut user).pop().unwrap();
+    let (tasks, _) = tokio::sync::mpsc::unbounded_channel();
+    let mut app = App::new(tasks);
+    app.screen = Screen::InGame(user);
+
+    // a reply from a game that's since been dropped, to the same position, is thrown away and the computer is still asked.
+    let stale = computer::think(computer::generation().wrapping_sub(1), asked.clone(), limits.clone(), engine);
+    computer::event(number, stale, &mut app);
+    let Screen::InGame(user) = &app.screen else { panic!() };
+    assert!(user.game.turn_white && user.thinking.is_some());
+
+    let fresh = computer::think(computer::generation(), asked, limits, engine);
+    computer::event(number, fresh, &mut app);
+    let Screen::InGame(user) = &app.screen else { panic!() };
+    assert!(!user.game.turn_white && user.thinking.is_none());
+}
+
+#[test]
+fn kriegspiel_umpire() {
+    // checks are announced by the line they come along.
+    let mut game = from_variant_fen(&Kriegspiel, "4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+    game.play(sq("a1"), sq("a8")).unwrap();
+    assert_eq!(announce(&game), [Announcement::Check(CheckLine::Rank)]);
+
+    let mut game = from_variant_fen(&Kriegspiel, "7k/8/8/8/8/8/8/2B1K3 w - - 0 1").unwrap();
+    game.play(sq("c1"), sq("b2")).unwrap();
+    assert_eq!(announce(&game), [Announcement::Check(CheckLine::LongDiagonal)]);
+
+    let mut game = from_variant_fen(&Kriegspiel, "4k3/8/8/8/8/5B2/8/4K3 w - - 0 1").unwrap();
+    game.play(sq("f3"), sq("h5")).unwrap();
+    assert_eq!(announce(&game), [Announcement::Check(CheckLine::ShortDiagonal)]);
+
+    // pawn tries, en pessant included, and then where the capture happened.
+    let mut game = from_variant_fen(&Kriegspiel, "4k3/8/8/8/3p4/8/4P3/4K3 w - - 0 1").unwrap();
+    game.play(sq("e2"), sq("e4")).unwrap();
+    assert_eq!(announce(&game), [Announcement::PawnTries(1)]);
+    let mut quiet = from_variant_fen(&Kriegspiel, &to_fen(&game)).unwrap();
+    quiet.play(sq("e8"), sq("d8")).unwrap();
+    assert_eq!(announce(&quiet), []);
+    game.play(sq("d4"), sq("e3")).unwrap();
+    assert_eq!(announce(&game), [Announcement::Capture("e3".to_string())]);
+    assert_eq!(Announcement::Capture("e3".to_string()).to_string(), "Capture on e3.");
+
+    // each side only sees its own pieces.
+    let seen = Kriegspiel.visible(&game.board, true);
+    assert!(seen[sq("e1")[1] as usize - 1][sq("e1")[0] as usize - 1]);
+    assert!(!seen[sq("e3")[1] as usize - 1][sq("e3")[0] as usize - 1]);
+}
+
+#[test]
+fn network_messages() {
+    let lines = ["e2e4", "e7e8n", "N@f3", "illegal"];
+    let messages = [
+        Message::Move(sq("e2"), sq("e4"), None),
+        Message::Move(sq("e7"), sq("e8"), Some(Promotion::Knight)),
+        Message::Drop(1, sq("f3")),
+        Message::Illegal
+    ];
+    for (line, message) in lines.into_iter().zip(messages) {
+        assert_eq!(Message::from_line(line, 8), Some(message));
+        assert_eq!(message.to_line(8), line);
+    }
+    assert_eq!(Message::from_line("e2e9", 8), None);
+    assert_eq!(Message::from_line("hello", 8), None);
+
+    // the tenth rank of a Grand chess board takes two digits.
+    let message = Message::Move([1, 1], [1, 2], None);
+    assert_eq!(message.to_line(10), "a10a9");
+    assert_eq!(Message::from_line("a10a9", 10), Some(message));
+}
+
+#[test]
+fn bughouse() {
+    // what white takes on one board goes to black on the other.
+    let mut first = from_variant_fen(&Bughouse, "4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1").unwrap();
+    let mut second = from_variant_fen(&Bughouse, "4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    first.play(sq("e4"), sq("d5")).unwrap();
+    settle(&mut first, &mut second);
+    assert_eq!(first.pockets, [[0; 5]; 2]);
+    assert_eq!(second.pockets, [[0; 5], [1, 0, 0, 0, 0]]);
+
+    // a check that something could be dropped in front of isn't mate yet.
+    let mut first = from_variant_fen(&Bughouse, "k7/8/8/8/8/8/6R1/1R2K3 w - - 0 1").unwrap();
+    first.play(sq("g2"), sq("g8")).unwrap();
+    assert_eq!(first.outcome, None);
+
+    // but a real mate ends the other board too, with the winners' partner winning there.
+    let mut first = from_variant_fen(&Bughouse, "k7/8/1Q6/8/8/8/8/4K3 w - - 0 1").unwrap();
+    first.play(sq("b6"), sq("b7")).unwrap();
+    assert_eq!(first.outcome, None);
+    let mut first = from_variant_fen(&Bughouse, "k7/2K5/1Q6/8/8/8/8/8 w - - 0 1").unwrap();
+    first.play(sq("b6"), sq("b7")).unwrap();
+    settle(&mut first, &mut second);
+    assert_eq!(first.outcome, Some(Outcome::Checkmate(true)));
+    assert_eq!(second.outcome, Some(Outcome::OtherBoard(false)));
+}
+
+#[test]
+fn computer_moves() {
+    let game = Game::new(&Standard, crate::board::STANDARD_BOARD, None);
+    assert_eq!(plays(&game).len(), 20);
+
+    // every way a pawn can promote, and every drop.
+    let game = from_variant_fen(&Crazyhouse, "4k3/P7/8/8/8/8/8/4K3[N] w - - 0 1").unwrap();
+    let all = plays(&game);
+    assert_eq!(all.iter().filter(|play| matches!(play, Play::Move(fromc, _, _) if *fromc == sq("a7"))).count(), 4);
+    assert_eq!(all.iter().filter(|play| matches!(play, Play::Drop(1, _))).count(), 61);
+
+    let mut game = from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+    search(&game, &Limits::depth(1), &mut Table::new(1), Options::default()).play.unwrap().play(&mut game).unwrap();
+    assert!(!game.turn_white);
+}
+
+#[test]
+fn engine_search() {
+    // takes a queen left hanging.
+    let game = from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+    assert_eq!(search(&game, &Limits::depth(2), &mut Table::new(1), Options::default()).play, Some(Play::Move(sq("d2"), sq("d5"), Promotion::Queen)));
+
+    // finds mate in one, and stops looking once it has.
+    let game = from_fen("6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+    let found = search(&game, &Limits::depth(4), &mut Table::new(1), Options::default());
+    assert_eq!(found.play, Some(Play::Move(sq("a1"), sq("a8"), Promotion::Queen)));
+    assert_eq!((found.score, found.depth), (MATE - 1, 1));
+
+    // sees the mate coming for black and doesn't walk into it, even with a pawn on offer.
+    let game = from_fen("6k1/5ppp/8/8/8/8/p4PPP/R5K1 b - - 0 1").unwrap();
+    assert!(search(&game, &Limits::depth(2), &mut Table::new(1), Options::default()).play.is_some_and(|play| play != Play::Move(sq("f7"), sq("f6"), Promotion::Queen)));
+    assert_eq!(search(&from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap(), &Limits::depth(3), &mut Table::new(1), Options::default()).play, None);
+}
+
+#[test]
+fn capablanca_and_grand() {
+    for (variant, fen) in [(&Capablanca as &dyn Variant, Capablanca::START), (&Grand, Grand::START)] {
+        let game = from_variant_fen(variant, fen).unwrap();
+        assert_eq!(Some(game.board), variant.start());
+        assert_eq!(to_fen(&game), fen);
+    }
+    assert_eq!(from_variant_fen(&Capablanca, "rnabqkbcnr/pppppppppp/9/10/10/10/PPPPPPPPPP/RNABQKBCNR w KQkq - 0 1").err(), Some(FenError::Placement));
+
+    // the archbishop moves as a bishop or a knight, and the chancellor as a rook or a knight.
+    let mut game = from_variant_fen(&Capablanca, "4k5/10/10/10/10/10/10/A4K2C1 w - - 0 1").unwrap();
+    assert!(game.play(sq("a1"), sq("b3")).is_ok());
+    assert!(game.play(sq("e8"), sq("d8")).is_ok());
+    assert_eq!(game.play(sq("i1"), sq("h2")), Err(IllegalMove::NotReachable));
+    assert!(game.play(sq("b3"), sq("f7")).is_ok());
+    assert!(in_check(&game.board, false));
+    assert!(game.play(sq("d8"), sq("c7")).is_ok());
+    assert!(game.play(sq("i1"), sq("h3")).is_ok());
+    assert!(game.play(sq("c7"), sq("c6")).is_ok());
+    assert!(game.play(sq("h3"), sq("h8")).is_ok());
+
+    // castling takes the king to the i file and the rook next to it on h.
+    let mut game = from_variant_fen(&Capablanca, "r4k3r/10/10/10/10/10/10/R4K3R w KQkq - 0 1").unwrap();
+    assert!(game.play([6, 8], [9, 8]).is_ok());
+    assert_eq!(game.board.squares[7][8], King(true, true));
+    assert_eq!(game.board.squares[7][7], Rook(true, true));
+
+    // Grand pawns can still skip from their third rank, and promote on the tenth.
+    let mut game = from_variant_fen(&Grand, Grand::START).unwrap();
+    assert!(game.play([1, 8], [1, 6]).is_ok());
+    let mut game = from_variant_fen(&Grand, "4k5/P9/10/10/10/10/10/10/10/4K5 w - - 0 1").unwrap();
+    assert!(game.play_promoting([1, 2], [1, 1], Promotion::Chancellor).is_ok());
+    assert_eq!(to_fen(&game), "C3k5/10/10/10/10/10/10/10/10/4K5 b - - 0 1");
+
+    // but only to pieces they've lost, and they can stay pawns short of the tenth rank.
+    // with nothing lost, a pawn can't go onto the tenth rank at all.
+    let full = "4k5/P9/10/P9/10/10/10/10/1NBQKCABN1/R8R w - - 0 1";
+    let mut game = from_variant_fen(&Grand, full).unwrap();
+    assert_eq!(game.clone().play([1, 2], [1, 1]), Err(IllegalMove::NotReachable));
+    assert_eq!(game.clone().play_promoting([1, 4], [1, 3], Promotion::Queen), Err(IllegalMove::CantPromoteTo));
+    assert_eq!(uci::find_play(&game, "a7a8"), Some(Play::Move([1, 4], [1, 3], Promotion::Pawn)));
+    assert!(game.play([1, 4], [1, 3]).is_ok());
+    assert_eq!(read_board(&game.board, [1, 3]), Some(Pawn(true, PawnState::Moved)));
+
+    let mut game = from_variant_fen(&Grand, "4k5/P9/10/P9/10/10/10/10/1NB1KCABN1/R8R w - - 0 1").unwrap();
+    assert_eq!(game.clone().play_promoting([1, 2], [1, 1], Promotion::Rook), Err(IllegalMove::CantPromoteTo));
+    assert_eq!(game.clone().play_promoting([1, 2], [1, 1], Promotion::Pawn), Err(IllegalMove::CantPromoteTo));
+    let names: Vec<String> = plays(&game).into_iter().filter(|play| matches!(play, Play::Move([1, 4], _, _))).map(|play| play.name(&game)).collect();
+    assert_eq!(names, vec!["a7a8q", "a7a8"]);
+    assert!(game.play([1, 2], [1, 1]).is_ok());
+    assert_eq!(read_board(&game.board, [1, 1]), Some(Queen(true)));
+}
+
+// the made up pieces are shared by every test, so the ones that add them take turns.
+static REGISTRY: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+#[test]
+fn betza_pieces() {
+    let _turn = REGISTRY.lock().unwrap_or_else(|e| e.into_inner());
+    // squares a piece can go to from 'from', sorted so they can be compared.
+    fn targets(board: &Board, from: &str) -> Vec<Loc> {
+        let piece = crate::board::read_board(board, sq(from)).unwrap();
+        let mut squares: Vec<Loc> = crate::piece::move_list(board, sq(from), piece).into_iter()
+            .filter(|movement| movement.relation != crate::types::Movement::Blocked).map(|movement| movement.to).collect();
+        squares.sort();
+        squares
+    }
+
+    // written out in Betza, the usual pieces move just like the built in ones.
+    for (notation, piece) in [("N", Knight(true)), ("R", Rook(true, true)), ("B", Bishop(true)), ("Q", Queen(true)), ("WW", Rook(true, true)), ("BN", Archbishop(true))] {
+        let index = betza::register(vec![FairyPiece::new('Y', notation).unwrap()]).unwrap()[0];
+        let pieces = [("d4", Fairy(true, index, true)), ("d6", Pawn(false, PawnState::Moved)), ("f4", Pawn(true, PawnState::Moved))];
+        let fairy = board_from(&pieces);
+        let usual = board_from(&[("d4", piece), pieces[1], pieces[2]]);
+        assert_eq!(targets(&fairy, "d4"), targets(&usual, "d4"), "{}", notation);
+    }
+
+    // forwards means up the board for white and down it for black.
+    let pawn = betza::register(vec![FairyPiece::new('Y', "fmWfcFifmnD").unwrap()]).unwrap()[0];
+    let board = board_from(&[("d2", Fairy(true, pawn, false)), ("e3", Knight(false)), ("d7", Fairy(false, pawn, true)), ("c6", Knight(true))]);
+    assert_eq!(targets(&board, "d2"), [sq("d4"), sq("d3"), sq("e3")]);
+    assert_eq!(targets(&board, "d7"), [sq("c6"), sq("d6")]);
+    let board = board_from(&[("d2", Fairy(true, pawn, false)), ("d3", Knight(false))]);
+    assert!(targets(&board, "d2").is_empty());
+
+    // a cannon moves like a rook but takes by hopping over one piece.
+    let cannon = betza::register(vec![FairyPiece::new('Y', "mRcpR").unwrap()]).unwrap()[0];
+    let board = board_from(&[("a1", Fairy(true, cannon, true)), ("a3", Pawn(true, PawnState::Moved)), ("a6", Pawn(false, PawnState::Moved)), ("c1", Knight(false))]);
+    assert_eq!(targets(&board, "a1"), [sq("a6"), sq("a2"), sq("b1")]);
+
+    // a lame knight is stopped by a piece right next to it, and a ranged one only goes so far.
+    let horse = betza::register(vec![FairyPiece::new('Y', "nN").unwrap()]).unwrap()[0];
+    let board = board_from(&[("e4", Fairy(true, horse, true)), ("e5", Pawn(true, PawnState::Moved))]);
+    assert_eq!(targets(&board, "e4").len(), 6);
+    let short = betza::register(vec![FairyPiece::new('Y', "W2").unwrap()]).unwrap()[0];
+    assert_eq!(targets(&board_from(&[("e4", Fairy(true, short, true))]), "e4").len(), 8);
+
+    // asking whether one takes on a square gives the same answer as going through all its moves.
+    let crowd = [("c3", Knight(false)), ("d6", Pawn(false, PawnState::Moved)), ("f5", Rook(false, true)), ("g2", Queen(false)), ("b5", Bishop(true))];
+    for piece in [pawn, cannon, horse, short] {
+        for moved in [false, true] {
+            let mut pieces = crowd.to_vec();
+            pieces.push(("d4", Fairy(true, piece, moved)));
+            let board = board_from(&pieces);
+            let takes = crate::piece::move_list(&board, sq("d4"), Fairy(true, piece, moved));
+            for (square, _) in crowd {
+                let expected = takes.iter().any(|movement| movement.to == sq(square) && movement.relation == crate::types::Movement::Enemy);
+                assert_eq!(betza::attacks(&board, sq("d4"), Fairy(true, piece, moved), sq(square)), expected, "{} {}", piece, square);
+            }
+        }
+    }
+
+    assert_eq!(betza::parse("fX"), Err(BetzaError::Atom('X')));
+    assert_eq!(betza::parse("qN"), Err(BetzaError::Modifier('q')));
+    assert_eq!(betza::parse("Nf"), Err(BetzaError::Dangling));
+    assert_eq!(betza::parse(""), Err(BetzaError::Empty));
+}
+
+#[test]
+fn variant_config() {
+    let _turn = REGISTRY.lock().unwrap_or_else(|e| e.into_inner());
+    let variant = config::parse("
+        # rooks that can also jump like a knight, standing in for the knights.
+        name = Jumping rooks
+        start = rjbqkbjr/pppppppp/8/8/8/8/PPPPPPPP/RJBQKBJR w KQkq - 0 1
+        piece = J RN
+        promotions = QR
+        castling = no
+    ").unwrap();
+    assert_eq!(variant.name(), "Jumping rooks");
+    assert!(!variant.castles());
+    assert_eq!(variant.promotions(), [Promotion::Queen, Promotion::Rook]);
+
+    let mut game = Game::new(variant, variant.start().unwrap(), None);
+    assert!(game.play(sq("b1"), sq("b3")).is_err());
+    assert!(game.play(sq("b1"), sq("a3")).is_ok());
+    assert!(game.play(sq("e7"), sq("e5")).is_ok());
+    assert!(game.play(sq("a3"), sq("a6")).is_ok());
+    assert_eq!(to_fen(&game), "rjbqkbjr/pppp1ppp/J7/4p3/8/8/PPPPPPPP/R1BQKBJR b - - 1 2");
+    assert_eq!(from_variant_fen(variant, &to_fen(&game)).map(|game| game.board), Ok(game.board));
+
+    assert!(matches!(config::parse("start = 8/8/8/8/8/8/8/8 w - - 0 1"), Err(ConfigError::Name)));
+    assert!(matches!(config::parse("name = x\npiece = J fZ9q"), Err(ConfigError::Piece(2, BetzaError::Modifier('q')))));
+    assert!(matches!(config::parse("name = x\nstart = 8/8/8/8/8/8/8/8 w - - 0 1"), Err(ConfigError::Start(FenError::Placement))));
+    assert!(matches!(config::parse("name = x\nspeed = fast"), Err(ConfigError::Line(2))));
+    assert!(matches!(config::parse("name = x\npiece = n W"), Err(ConfigError::Letter(2, 'N'))));
+    assert!(matches!(config::parse("name = x\npiece = J W\npiece = J F"), Err(ConfigError::Letter(3, 'J'))));
+
+    // a variant that doesn't work gives its pieces back, and pieces that don't all fit don't go in at all.
+    let next = || {
+        let index = betza::register(vec![FairyPiece::new('Y', "W").unwrap()]).unwrap()[0];
+        betza::unregister(&[index]);
+        index
+    };
+    let free = next();
+    assert!(matches!(config::parse("name = x\npiece = J W\nstart = 8/8/8/8/8/8/8/8 w - - 0 1"), Err(ConfigError::Start(FenError::Placement))));
+    assert!(matches!(config::parse("name = x\npiece = J W\npiece = Z fZ9q"), Err(ConfigError::Piece(3, _))));
+    assert_eq!(next(), free);
+    let too_many = (0..=u8::MAX as usize + 1).map(|_| FairyPiece::new('Y', "W").unwrap()).collect();
+    assert_eq!(betza::register(too_many), Err(BetzaError::TooMany));
+    assert_eq!(next(), free);
+}
+
+#[test]
+fn evaluation() {
+    let start = Game::new(&Standard, crate::board::STANDARD_BOARD, None);
+    let terms = eval::breakdown(&start.board);
+    assert_eq!((terms.total(), terms.phase), (0, eval::PHASE));
+    assert_eq!(eval::breakdown(&from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap().board).phase, 0);
+
+    // the same position with the colours swapped scores the same for whoever is to move.
+    let mirror = |fen: &str| {
+        let fields: Vec<&str> = fen.split(' ').collect();
+        let placement: Vec<String> = fields[0].split('/').rev()
+            .map(|rank| rank.chars().map(|c| if c.is_ascii_uppercase() {c.to_ascii_lowercase()} else {c.to_ascii_uppercase()}).collect()).collect();
+        format!("{} {} - - 0 1", placement.join("/"), if fields[1] == "w" {"b"} else {"w"})
+    };
+    for fen in ["r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w - - 0 1", "6k1/5pp1/7p/3P4/8/2B5/5PPP/6K1 b - - 0 1"] {
+        let (game, flipped) = (from_fen(fen).unwrap(), from_fen(&mirror(fen)).unwrap());
+        assert_eq!(eval::evaluate(&game), eval::evaluate(&flipped), "{}", fen);
+        assert_eq!(eval::breakdown(&game.board).total(), -eval::breakdown(&flipped.board).total());
+    }
+
+    // being a queen up is good for whoever has it.
+    assert!(eval::evaluate(&from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap()) > 800);
+    assert!(eval::evaluate(&from_fen("4k3/8/8/8/8/8/8/3QK3 b - - 0 1").unwrap()) < -800);
+
+    // doubled and isolated pawns, and a passed one.
+    let terms = eval::breakdown(&from_fen("4k3/1p6/8/8/8/P7/P7/4K3 w - - 0 1").unwrap().board);
+    assert_eq!(terms.pawns, Score { mg: -25, eg: -30 });
+    let terms = eval::breakdown(&from_fen("4k3/8/4P3/8/8/8/8/4K3 w - - 0 1").unwrap().board);
+    assert_eq!(terms.pawns, Score { mg: 5, eg: 38 });
+
+    let terms = eval::breakdown(&from_fen("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1").unwrap().board);
+    assert_eq!(terms.bishop_pair, Score { mg: 30, eg: 50 });
+    assert!(terms.to_string().contains("Bishop pair"));
+
+    // a king behind its pawns is safer than one out in the open with a queen next to it.
+    let terms = eval::breakdown(&from_fen("4k3/8/8/8/8/8/5PPP/q5K1 w - - 0 1").unwrap().board);
+    let exposed = eval::breakdown(&from_fen("4k3/8/8/8/8/8/8/q3K3 w - - 0 1").unwrap().board);
+    assert!(terms.king_safety.mg > exposed.king_safety.mg);
+}
+
+#[test]
+fn transposition_table() {
+    // the same position reached two ways is the same position, but not with the other side to move.
+    let mut one = Game::new(&Standard, crate::board::STANDARD_BOARD, None);
+    let mut two = one.clone();
+    for (fromc, toc) in [("g1", "f3"), ("g8", "f6"), ("b1", "c3"), ("b8", "c6")] {
+        one.play(sq(fromc), sq(toc)).unwrap();
+    }
+    for (fromc, toc) in [("b1", "c3"), ("b8", "c6"), ("g1", "f3"), ("g8", "f6")] {
+        two.play(sq(fromc), sq(toc)).unwrap();
+    }
+    assert_eq!(table::hash(&one), table::hash(&two));
+    two.turn_white = false;
+    assert_ne!(table::hash(&one), table::hash(&two));
+
+    // a mate found deep in one search is just as far away when the position comes up elsewhere.
+    let mut table = Table::new(1);
+    table.store(7, 3, Bound::Exact, MATE - 5, 2, None);
+    let entry = table.probe(7).unwrap();
+    assert_eq!((entry.score(2), entry.score(4), entry.depth, entry.bound), (MATE - 5, MATE - 7, 3, Bound::Exact));
+    assert_eq!(table.probe(8), None);
+    assert_eq!((table.stats.probes, table.stats.hits, table.stats.stores), (2, 1, 1));
+    assert_eq!(table.fill(), 1);
+
+    // searching again with what the last search remembered finds the same thing in fewer nodes.
+    let game = from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3").unwrap();
+    let mut table = Table::new(1);
+    let first = search(&game, &Limits::depth(3), &mut table, Options::default());
+    let again = search(&game, &Limits::depth(3), &mut table, Options::default());
+    assert_eq!((first.play, first.score), (again.play, again.score));
+    assert!(again.nodes < first.nodes);
+    assert!(table.stats.hit_rate() > 0.0);
+    assert_eq!(Table::new(2).len(), Table::new(1).len() * 2);
+}
+
+#[test]
+fn search_heuristics() {
+    let mut none = Options::default();
+    for name in Options::NAMES {
+        assert!(none.set(name, false));
+    }
+    assert!(!none.set("luck", true));
+
+    // one move deep, taking a defended pawn looks like winning it, unless the search follows the recapture.
+    let game = from_fen("4k3/8/3p4/4p3/8/8/8/4QK2 w - - 0 1").unwrap();
+    let grab = Play::Move(sq("e1"), sq("e5"), Promotion::Queen);
+    assert_eq!(search(&game, &Limits::depth(1), &mut Table::new(1), none).play, Some(grab));
+    assert_ne!(search(&game, &Limits::depth(1), &mut Table::new(1), Options::default()).play, Some(grab));
+    assert_eq!(see::see(&game, grab), -850);
+    assert!(order::losing(&game, grab));
+
+    // a free piece is worth all of itself, and en passant takes a pawn too.
+    let game = from_fen("4k3/8/8/3pP1n1/8/8/8/2B1K3 w - d6 0 1").unwrap();
+    assert_eq!(see::see(&game, Play::Move(sq("c1"), sq("g5"), Promotion::Queen)), 320);
+    assert_eq!(order::captured(&game, Play::Move(sq("e5"), sq("d6"), Promotion::Queen)), Some(Pawn(false, PawnState::Moved)));
+
+    // everything on or off, the bench positions with a mate in them still find it.
+    for options in [none, Options::default()] {
+        let game = from_fen(bench::POSITIONS[2]).unwrap();
+        let found = search(&game, &Limits::depth(2), &mut Table::new(1), options);
+        assert_eq!(found.play.map(|play| play.name(&game)), Some("h5f7".to_string()));
+        assert_eq!(found.score, MATE - 1);
+    }
+    let game = from_variant_fen(&Crazyhouse, "4k3/P7/8/8/8/8/8/4K3[N] w - - 0 1").unwrap();
+    assert_eq!(Play::Move(sq("a7"), sq("a8"), Promotion::Knight).name(&game), "a7a8n");
+    assert_eq!(Play::Drop(1, sq("f3")).name(&game), "N@f3");
+}
+
+#[test]
+fn static_exchange() {
+    // a rook behind a rook counts once the one in front has taken.
+    let game = from_fen("3rk3/3r4/8/3p4/8/8/3R4/3RK3 w - - 0 1").unwrap();
+    let take = Play::Move(sq("d2"), sq("d5"), Promotion::Queen);
+    assert_eq!(see::see(&game, take), 100 - 500);
+    let game = from_fen("3rk3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1").unwrap();
+    assert_eq!(see::see(&game, take), 100);
+
+    // a bishop behind a queen, and a pawn that isn't worth taking back with.
+    let game = from_fen("4k3/8/2p5/3n4/8/5Q2/6B1/4K3 w - - 0 1").unwrap();
+    assert_eq!(see::see(&game, Play::Move(sq("f3"), sq("d5"), Promotion::Queen)), 320 - 950 + 100);
+
+    // the king can only take back when nothing else is guarding the square.
+    let game = from_fen("4k3/4p3/8/8/8/8/8/K3R3 w - - 0 1").unwrap();
+    assert_eq!(see::see(&game, Play::Move(sq("e1"), sq("e7"), Promotion::Queen)), 100 - 500);
+    let game = from_fen("4k3/4p3/8/8/8/8/4R3/K3R3 w - - 0 1").unwrap();
+    assert_eq!(see::see(&game, Play::Move(sq("e2"), sq("e7"), Promotion::Queen)), 100);
+
+    // moving a knight where a pawn takes it loses the knight, and a safe square loses nothing.
+    let game = from_fen("4k3/8/3p4/8/8/8/8/4K1N1 w - - 0 1").unwrap();
+    assert_eq!(see::see(&game, Play::Move(sq("g1"), sq("e2"), Promotion::Queen)), 0);
+    let game = from_fen("4k3/8/3p4/8/3N4/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(see::see(&game, Play::Move(sq("d4"), sq("e5"), Promotion::Queen)), -320);
+
+    // the queen attacked by a pawn is hanging and the defended rook isn't, and black's knight goes before its pawn.
+    let game = from_fen("4k3/8/8/2p5/1Q6/8/1R3n2/1R2K3 b - - 0 1").unwrap();
+    assert_eq!(see::hanging(&game, true), vec![sq("b4")]);
+    assert_eq!(see::hanging(&game, false), vec![sq("f2"), sq("c5")]);
+    assert_eq!(crate::check::attackers(&game.board, sq("f2"), false), vec![sq("b2"), sq("e1")]);
+}
+
+#[test]
+fn search_limits() {
+    let game = from_fen(bench::POSITIONS[1]).unwrap();
+
+    // stops on the node it was told to, with a move to show for it.
+    let limits = Limits { nodes: Some(500), ..Limits::default() };
+    let found = search(&game, &limits, &mut Table::new(1), Options::default());
+    assert_eq!(found.nodes, 500);
+    assert!(found.play.is_some_and(|play| plays(&game).contains(&play)));
+
+    // stopped before it starts, it still has a move.
+    let limits = Limits::default();
+    limits.stop.stop();
+    let found = search(&game, &limits, &mut Table::new(1), Options::default());
+    assert_eq!((found.depth, found.play.is_some()), (0, true));
+
+    // stopped from another thread part way through a search with no limits of its own.
+    // the bounds on time are loose, since they only have to catch a search that doesn't stop at all.
+    let limits = Limits::default();
+    let stop = limits.stop.clone();
+    let thinking = std::thread::spawn(move || search(&game, &limits, &mut Table::new(1), Options::default()));
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    let start = std::time::Instant::now();
+    stop.stop();
+    let found = thinking.join().unwrap();
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    assert!(found.play.is_some());
+
+    // with no time left it has to stop straight away, and with no limits it never runs out.
+    let limits = Limits { time: [Some(std::time::Duration::ZERO), None], ..Limits::default() };
+    assert!(TimeManager::new(&limits, true).out_of_time());
+    assert!(!TimeManager::new(&limits, false).out_of_time() && TimeManager::new(&limits, false).another());
+    let limits = Limits { movetime: Some(std::time::Duration::from_millis(200)), ..Limits::default() };
+    let start = std::time::Instant::now();
+    search(&from_fen(bench::POSITIONS[0]).unwrap(), &limits, &mut Table::new(1), Options::default());
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+}
+
+#[test]
+fn uci_engine() {
+    let buffer = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let out: Out = buffer.clone();
+    let mut engine = Engine::new(out);
+    let said = |from: usize| String::from_utf8(buffer.lock().unwrap()[from..].to_vec()).unwrap();
+
+    engine.command("uci");
+    engine.command("isready");
+    let reply = said(0);
+    assert!(reply.starts_with("id name chess") && reply.ends_with("uciok\nreadyok\n"));
+    assert!(reply.contains("option name UCI_Variant type combo default chess var chess") && reply.contains("var atomic"));
+
+    // the moves are played through the rules, and it finds the mate they lead to.
+    let from = buffer.lock().unwrap().len();
+    engine.command("position startpos moves e2e4 e7e5 f1c4 b8c6 d1h5 g8f6");
+    engine.command("go mate 1");
+    engine.wait();
+    let reply = said(from);
+    assert!(reply.contains("info depth 1 seldepth ") && reply.contains(" score mate 1 ") && reply.contains(" hashfull "));
+    assert!(reply.contains(" pv h5f7\n") && reply.ends_with("bestmove h5f7\n"));
+
+    // the same from a FEN, told which moves to choose between, with a reply to ponder on.
+    let from = buffer.lock().unwrap().len();
+    engine.command(&format!("position fen {}", bench::POSITIONS[2]));
+    engine.command("go depth 3 searchmoves c4b5 h5g4");
+    engine.wait();
+    let last = said(from).lines().last().unwrap().to_string();
+    assert!(last.starts_with("bestmove c4b5 ponder ") || last.starts_with("bestmove h5g4 ponder "), "{}", last);
+
+    // an illegal move and an unknown option are complained about, and it keeps going.
+    let from = buffer.lock().unwrap().len();
+    engine.command("position startpos moves e2e5");
+    engine.command("setoption name Wibble value true");
+    engine.command("setoption name null-move value false");
+    assert_eq!(said(from), "info string e2e5 isn't a legal move\ninfo string there's no option called Wibble\n");
+
+    // searching forever, or pondering, it waits to be told before giving its move.
+    let from = buffer.lock().unwrap().len();
+    engine.command("go infinite");
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    assert!(!said(from).contains("bestmove"));
+    engine.command("stop");
+    assert!(said(from).contains("bestmove "));
+    let from = buffer.lock().unwrap().len();
+    engine.command("go ponder wtime 100 btime 100");
+    std::thread::sleep(std::time::Duration::from_millis(300));
+    assert!(!said(from).contains("bestmove"));
+    engine.command("ponderhit");
+    engine.wait();
+    assert!(said(from).contains("bestmove "));
+
+    // changing the table part way through a search that never ends by itself stops it first, rather than waiting on it.
+    let from = buffer.lock().unwrap().len();
+    engine.command("go infinite");
+    engine.command("setoption name Hash value 1");
+    assert!(said(from).contains("bestmove "));
+    engine.command("stop");
+    engine.command("isready");
+    assert!(said(from).ends_with("readyok\n"));
+
+    // other variants' moves are checked by their own rules.
+    let from = buffer.lock().unwrap().len();
+    engine.command("setoption name UCI_Variant value crazyhouse");
+    engine.command("position startpos moves e2e4 d7d5 e4d5 d8d5 P@e4");
+    engine.command("go nodes 1000");
+    assert!(!engine.command("quit"));
+    assert!(said(from).contains("bestmove ") && !said(from).contains("info string"));
+    let game = from_fen(bench::POSITIONS[0]).unwrap();
+    assert!(uci::find_play(&game, "E2E4").is_some() && uci::find_play(&game, "e2e5").is_none());
+}
+
+// a stand-in for a real engine, which says the same things whatever it's sent, and writes down what that was.
+const STAND_IN: &str = r#"#!/bin/sh
+while read -r line; do
+    echo "$line" >> "$0.log"
+    case "$line" in
+        uci) echo "id name Stand-in 1.0"
+             echo "option name Hash type spin default 16 min 1 max 1024"
+             echo "option name Clear Hash type button"
+             echo "option name UCI_Variant type combo default chess var chess var atomic"
+             echo "uciok" ;;
+        isready) echo "readyok" ;;
+        "go infinite") echo "info depth 1 score cp 20 pv e2e4"
+                       read -r line
+                       echo "$line" >> "$0.log"
+                       echo "bestmove e2e4" ;;
+        "go nodes 1") ;;
+        go*) echo "info depth 1 seldepth 1 score cp 35 nodes 20 pv e2e4 e7e5 e2e4"
+             echo "info string thinking hard"
+             echo "info depth 2 score mate -3 pv d2d4"
+             echo "bestmove d2d4 ponder d7d5" ;;
+        quit) exit 0 ;;
+    esac
+done
+"#;
+
+#[test]
+fn uci_client() {
+    use std::os::unix::fs::PermissionsExt;
+    let path = std::env::temp_dir().join(format!("chess-stand-in-{}", std::process::id()));
+    let log = path.with_extension("log");
+    std::fs::write(&path, STAND_IN).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let _ = std::fs::remove_file(&log);
+    let path = path.to_str().unwrap();
+
+    assert!(matches!(Client::start("/nonexistent/engine", &[]), Err(ClientError::Spawn(_))));
+    assert!(matches!(Client::start(path, &[("Threads".to_string(), "4".to_string())]), Err(ClientError::NoOption(_))));
+
+    // the handshake, with its options set by name whatever the case.
+    let mut engine = Client::start(path, &[("hash".to_string(), "32".to_string()), ("Clear Hash".to_string(), String::new())]).unwrap();
+    assert_eq!(engine.name, "Stand-in 1.0");
+    assert_eq!(engine.option("uci_variant").unwrap().vars, vec!["chess", "atomic"]);
+
+    // what it thinks along the way, with the lines cut off at the first move that isn't legal.
+    let game = from_fen(bench::POSITIONS[0]).unwrap();
+    let mut infos = Vec::new();
+    let limits = Limits { movetime: Some(std::time::Duration::from_millis(250)), ..Limits::default() };
+    let play = engine.go(&game, &limits, |info| infos.push(info)).unwrap();
+    assert_eq!(play, uci::find_play(&game, "d2d4"));
+    assert_eq!(infos.len(), 2);
+    assert_eq!((infos[0].depth, infos[0].score, infos[0].pv.len()), (1, client::Score::Centipawns(35), 2));
+    assert_eq!((infos[1].score, infos[1].score.for_white(false).to_string()), (client::Score::Mate(-3), "#3".to_string()));
+
+    // with no limits it goes until it's stopped.
+    let limits = Limits::default();
+    limits.stop.stop();
+    assert_eq!(engine.go(&game, &limits, |_| ()).unwrap(), uci::find_play(&game, "e2e4"));
+
+    // it's told about the variant when it has it, and refuses when it doesn't.
+    let atomic = from_variant_fen(crate::variant::find("atomic").unwrap(), bench::POSITIONS[0]).unwrap();
+    engine.go(&atomic, &Limits::depth(1), |_| ()).unwrap();
+    let crazyhouse = from_variant_fen(crate::variant::find("crazyhouse").unwrap(), bench::POSITIONS[0]).unwrap();
+    assert!(matches!(engine.go(&crazyhouse, &Limits::depth(1), |_| ()), Err(ClientError::Variant(_))));
+
+    // one that never gives its move, even when it's told to stop, is given up on.
+    let limits = Limits { nodes: Some(1), ..Limits::default() };
+    limits.stop.stop();
+    assert!(matches!(engine.go(&game, &limits, |_| ()), Err(ClientError::Silent("bestmove"))));
+    drop(engine);
+
+    let sent = std::fs::read_to_string(&log).unwrap();
+    let _ = std::fs::remove_file(&log);
+    let _ = std::fs::remove_file(path);
+    assert!(sent.starts_with("uci\nquit\nuci\nsetoption name Hash value 32\nsetoption name Clear Hash\nisready\n"), "{}", sent);
+    assert!(sent.contains(&format!("position fen {}\ngo movetime 250\n", bench::POSITIONS[0])));
+    assert!(sent.contains("go infinite\nstop\n"));
+    assert!(sent.contains("setoption name UCI_Variant value atomic\nucinewgame\nisready\n"));
+    assert!(sent.contains("go depth 1\n"));
+    assert!(sent.ends_with("go nodes 1\nstop\nquit\n"));
 
+    let limits = Limits { time: [Some(std::time::Duration::from_secs(60)), Some(std::time::Duration::from_secs(30))], moves_to_go: Some(20), ..Limits::default() };
+    assert_eq!(client::go_command(&limits), "go wtime 60000 winc 0 btime 30000 binc 0 movestogo 20");
 }
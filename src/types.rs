@@ -11,6 +11,11 @@ pub enum Space {
     Bishop(bool),
     Queen(bool),
     King(bool, bool),
+    // a bishop and a knight in one, and a rook and a knight in one, from Capablanca and Grand chess.
+    Archbishop(bool),
+    Chancellor(bool),
+    // a piece made up in Betza notation, by its number in the betza module, and whether it has moved yet.
+    Fairy(bool, u8, bool),
     Open
 }
 
@@ -24,6 +29,9 @@ impl Space {
             Bishop(w) => *w,
             Queen(w) => *w,
             King(w, _) => *w,
+            Archbishop(w) => *w,
+            Chancellor(w) => *w,
+            Fairy(w, _, _) => *w,
             Open => {panic!("tried to get the team of an empty space!")}
         }
     }
@@ -38,11 +46,53 @@ impl From<Space> for char {
             Bishop(_) => 'B',
             Queen(_) => 'Q',
             King(_, _) => 'K',
+            Archbishop(_) => 'A',
+            Chancellor(_) => 'C',
+            Fairy(_, index, _) => crate::betza::piece(index).letter,
             Open => ' '
         }
     }
 }
 
+// what a pawn can turn into when it reaches the far end.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Promotion {
+    Queen,
+    Rook,
+    Bishop,
+    Knight,
+    King,
+    Archbishop,
+    Chancellor,
+    // staying a pawn, where promoting is up to the player.
+    Pawn
+}
+
+impl Promotion {
+
+    pub fn piece(&self, is_white: bool) -> Space {
+        match self {
+            Promotion::Queen => Queen(is_white),
+            Promotion::Rook => Rook(is_white, true),
+            Promotion::Bishop => Bishop(is_white),
+            Promotion::Knight => Knight(is_white),
+            Promotion::King => King(is_white, true),
+            Promotion::Archbishop => Archbishop(is_white),
+            Promotion::Chancellor => Chancellor(is_white),
+            Promotion::Pawn => Pawn(is_white, PawnState::Moved)
+        }
+    }
+
+    pub fn letter(&self) -> char {
+        self.piece(true).into()
+    }
+
+    pub fn from_letter(letter: char) -> Option<Promotion> {
+        [Promotion::Queen, Promotion::Rook, Promotion::Bishop, Promotion::Knight, Promotion::King, Promotion::Archbishop, Promotion::Chancellor, Promotion::Pawn]
+            .into_iter().find(|promotion| promotion.letter() == letter.to_ascii_uppercase())
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum PawnState {
     NotMoved,
@@ -59,7 +109,50 @@ pub enum Movement {
     QueenSide,
     Check,
     Blocked,
-    EnPessant
+    EnPessant,
+    // a piece coming out of a Crazyhouse pocket; the 'from' square doesn't mean anything.
+    Drop
+}
+
+// the reasons a move can be turned down.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IllegalMove {
+    WrongSide,
+    NoPiece,
+    NotReachable,
+    Pinned,
+    KingInCheck,
+    CastlingRightsLost,
+    CastlingThroughCheck,
+    NothingToDrop,
+    DropOnPiece,
+    PawnDropRank,
+    ExplodesOwnKing,
+    CantPromoteTo,
+    MustCapture,
+    GivesCheck
+}
+
+impl std::fmt::Display for IllegalMove {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use IllegalMove::*;
+        f.write_str(match self {
+            WrongSide => "That piece belongs to the other side.",
+            NoPiece => "There is no piece there.",
+            NotReachable => "That piece can't reach that square.",
+            Pinned => "That piece is pinned to its king.",
+            KingInCheck => "That would leave your king in check.",
+            CastlingRightsLost => "The king or rook has already moved.",
+            CastlingThroughCheck => "The king can't castle out of or through check.",
+            NothingToDrop => "You don't have one of those to drop.",
+            DropOnPiece => "Pieces can only be dropped on empty squares.",
+            PawnDropRank => "Pawns can't be dropped on the first or last rank.",
+            ExplodesOwnKing => "That would blow up your own king.",
+            CantPromoteTo => "Pawns can't promote to that piece here.",
+            MustCapture => "You have to take a piece when you can.",
+            GivesCheck => "Giving check isn't allowed in this variant."
+        })
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -100,7 +193,7 @@ impl Direction {
     pub fn translate(&self, loc: Loc, d: isize) -> Loc {
 
         use Direction::*;
-        let out = match self {
+        match self {
             North => loc.add([0, d]),
             South => loc.add([0, -d]),
             East => loc.add([d, 0]),
@@ -109,7 +202,6 @@ impl Direction {
             NE => loc.add([d,d]),
             SW => loc.add([-d, -d]),
             SE => loc.add([d, -d])
-        };
-        out
+        }
     }
 }
@@ -0,0 +1,344 @@
+use std::{
+    fmt,
+    io::{self, BufRead, BufReader, Write},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant}
+};
+use crate::{
+    engine::{Play, time::Limits},
+    fen::to_fen,
+    game::{Game, side}
+};
+use super::{CHESS, find_play, variant_name};
+
+
+// the other end of UCI: another engine, run as a program of its own, that plays or analyses for us.
+// it's sent the position as a FEN each time, so it doesn't need to have followed the game.
+
+// how long it gets to start up and answer, before it's taken to be broken.
+const HANDSHAKE: Duration = Duration::from_secs(10);
+// how often a search is checked on, to see whether it's been called off.
+const POLL: Duration = Duration::from_millis(10);
+// how long it gets to give its move once it's told to stop.
+const STOPPING: Duration = Duration::from_secs(2);
+// how long it gets to quit by itself before it's killed.
+const QUIT: Duration = Duration::from_millis(500);
+
+#[derive(Debug)]
+pub enum ClientError {
+    Spawn(io::Error),
+    // it quit, or stopped reading what it's sent.
+    Gone,
+    // it didn't say the thing it had to in time.
+    Silent(&'static str),
+    // it doesn't play the variant, going by its UCI_Variant option.
+    Variant(String),
+    NoOption(String)
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Spawn(e) => write!(f, "Couldn't start the engine ({})", e.kind()),
+            ClientError::Gone => f.write_str("The engine quit"),
+            ClientError::Silent(what) => write!(f, "The engine never said {}", what),
+            ClientError::Variant(name) => write!(f, "The engine doesn't play {}", name),
+            ClientError::NoOption(name) => write!(f, "The engine has no option called {}", name)
+        }
+    }
+}
+
+// something the engine says can be changed, from its "option" lines.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EngineOption {
+    pub name: String,
+    // the choices, for a combo.
+    pub vars: Vec<String>
+}
+
+// "option name <name> type <kind> [default <x>] [min <x>] [max <x>] [var <x>]...", where the values can have spaces in them.
+fn parse_option(words: &[&str]) -> Option<EngineOption> {
+    const KEYS: [&str; 6] = ["name", "type", "default", "min", "max", "var"];
+    let mut option = EngineOption::default();
+
+    let mut i = 0;
+    while i < words.len() {
+        let key = words[i];
+        let end = (i + 1..words.len()).find(|j| KEYS.contains(&words[*j])).unwrap_or(words.len());
+        let value = words[i + 1..end].join(" ");
+        match key {
+            "name" => option.name = value,
+            "var" => option.vars.push(value),
+            _ => ()
+        }
+        i = end;
+    }
+    (!option.name.is_empty()).then_some(option)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Score {
+    Centipawns(i32),
+    // in moves, negative when it's the side to move getting mated.
+    Mate(i32)
+}
+
+impl Score {
+    // the score from White's side, given whose move it was.
+    pub fn for_white(self, turn_white: bool) -> Score {
+        match (self, turn_white) {
+            (score, true) => score,
+            (Score::Centipawns(cp), false) => Score::Centipawns(-cp),
+            (Score::Mate(moves), false) => Score::Mate(-moves)
+        }
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Score::Centipawns(cp) => write!(f, "{:+.2}", *cp as f64 / 100.0),
+            Score::Mate(moves) => write!(f, "#{}", moves)
+        }
+    }
+}
+
+// what the engine thinks so far, from one of its "info" lines. the score is from the side to move's point of view.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Info {
+    pub depth: u32,
+    pub score: Score,
+    pub pv: Vec<Play>
+}
+
+// only the lines with a score and a line of play are any use; the rest are about the current move and so on.
+// the line is checked against the rules, and ends at the first move that isn't legal.
+fn parse_info(game: &Game, words: &[&str]) -> Option<Info> {
+    let mut depth = 0;
+    let mut score = None;
+    let mut pv = Vec::new();
+
+    let mut rest = words.iter();
+    while let Some(word) = rest.next() {
+        match *word {
+            "depth" => depth = rest.next()?.parse().ok()?,
+            "score" => score = match (rest.next(), rest.next().map(|n| n.parse::<i32>())) {
+                (Some(&"cp"), Some(Ok(cp))) => Some(Score::Centipawns(cp)),
+                (Some(&"mate"), Some(Ok(moves))) => Some(Score::Mate(moves)),
+                _ => {return None;}
+            },
+            // the rest of the line is the moves.
+            "pv" => {
+                let mut game = game.clone();
+                for name in rest.by_ref() {
+                    match find_play(&game, name) {
+                        Some(play) if play.play(&mut game).is_ok() => pv.push(play),
+                        _ => {break;}
+                    }
+                }
+            },
+            // said in words, and to the end of the line.
+            "string" => {return None;},
+            _ => ()
+        }
+    }
+    match (score, pv.is_empty()) {
+        (Some(score), false) => Some(Info { depth, score, pv }),
+        _ => None
+    }
+}
+
+// "go" with the same limits the computer's own search takes. with none at all, it goes until it's stopped.
+pub fn go_command(limits: &Limits) -> String {
+    let mut go = vec!["go".to_string()];
+    let mut add = |key: &str, value: u128| go.push(format!("{} {}", key, value));
+
+    if let Some(depth) = limits.depth {
+        add("depth", depth as u128);
+    }
+    if let Some(nodes) = limits.nodes {
+        add("nodes", nodes as u128);
+    }
+    if let Some(movetime) = limits.movetime {
+        add("movetime", movetime.as_millis());
+    }
+    for (is_white, key, inc) in [(true, "wtime", "winc"), (false, "btime", "binc")] {
+        if let Some(time) = limits.time[side(is_white)] {
+            add(key, time.as_millis());
+            add(inc, limits.increment[side(is_white)].as_millis());
+        }
+    }
+    if let Some(moves) = limits.moves_to_go {
+        add("movestogo", moves as u128);
+    }
+
+    match go.len() {
+        1 => "go infinite".to_string(),
+        _ => go.join(" ")
+    }
+}
+
+pub struct Client {
+    process: Child,
+    input: ChildStdin,
+    // what it says, a line at a time, read on a thread of its own.
+    output: Receiver<String>,
+    pub name: String,
+    pub options: Vec<EngineOption>,
+    // the variant it's been told it's playing.
+    variant: String
+}
+
+impl Client {
+
+    // starts the engine at 'path' and sets the given options, once it's said what it has.
+    pub fn start(path: &str, options: &[(String, String)]) -> Result<Client, ClientError> {
+        let mut process = Command::new(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(ClientError::Spawn)?;
+
+        let input = process.stdin.take().ok_or(ClientError::Gone)?;
+        let stdout = process.stdout.take().ok_or(ClientError::Gone)?;
+        let (lines, output) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if lines.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let name = path.rsplit('/').next().unwrap_or(path).to_string();
+        let mut client = Client { process, input, output, name, options: Vec::new(), variant: CHESS.to_string() };
+
+        client.send("uci")?;
+        let deadline = Instant::now() + HANDSHAKE;
+        loop {
+            let line = client.line(deadline, "uciok")?;
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words.as_slice() {
+                ["id", "name", name @ ..] if !name.is_empty() => client.name = name.join(" "),
+                ["option", rest @ ..] => client.options.extend(parse_option(rest)),
+                ["uciok", ..] => {break;},
+                _ => ()
+            }
+        }
+
+        for (name, value) in options {
+            client.set_option(name, value)?;
+        }
+        client.ready()?;
+        Ok(client)
+    }
+
+    fn send(&mut self, command: &str) -> Result<(), ClientError> {
+        writeln!(self.input, "{}", command).and_then(|_| self.input.flush()).map_err(|_| ClientError::Gone)
+    }
+
+    // the next thing it says, which has to come before the deadline.
+    fn line(&self, deadline: Instant, waiting_for: &'static str) -> Result<String, ClientError> {
+        match self.output.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(line) => Ok(line),
+            Err(RecvTimeoutError::Timeout) => Err(ClientError::Silent(waiting_for)),
+            Err(RecvTimeoutError::Disconnected) => Err(ClientError::Gone)
+        }
+    }
+
+    // waits until it's done with everything it's been sent.
+    pub fn ready(&mut self) -> Result<(), ClientError> {
+        self.send("isready")?;
+        let deadline = Instant::now() + HANDSHAKE;
+        while self.line(deadline, "readyok")?.trim() != "readyok" {}
+        Ok(())
+    }
+
+    pub fn option(&self, name: &str) -> Option<&EngineOption> {
+        self.options.iter().find(|option| option.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn set_option(&mut self, name: &str, value: &str) -> Result<(), ClientError> {
+        let name = match self.option(name) {
+            Some(option) => option.name.clone(),
+            None => {return Err(ClientError::NoOption(name.to_string()));}
+        };
+        match value.is_empty() {
+            true => self.send(&format!("setoption name {}", name)),
+            false => self.send(&format!("setoption name {} value {}", name, value))
+        }
+    }
+
+    pub fn new_game(&mut self) -> Result<(), ClientError> {
+        self.send("ucinewgame")?;
+        self.ready()
+    }
+
+    // tells it the variant, if it's changed, and the position.
+    fn position(&mut self, game: &Game) -> Result<(), ClientError> {
+        let variant = variant_name(game.variant);
+        if variant != self.variant {
+            let plays = self.option("UCI_Variant").is_some_and(|option| option.vars.contains(&variant));
+            if !plays && variant != CHESS {
+                return Err(ClientError::Variant(game.variant.name().to_string()));
+            }
+            if plays {
+                self.send(&format!("setoption name UCI_Variant value {}", variant))?;
+            }
+            self.variant = variant;
+            self.new_game()?;
+        }
+        self.send(&format!("position fen {}", to_fen(game)))
+    }
+
+    // has it search the game to the limits, telling 'info' what it thinks along the way, and gives back its move.
+    // stopping the limits stops it, and it still gives a move, or it's given up on. one it names that isn't legal counts as none.
+    pub fn go(&mut self, game: &Game, limits: &Limits, mut info: impl FnMut(Info)) -> Result<Option<Play>, ClientError> {
+        self.position(game)?;
+        self.send(&go_command(limits))?;
+
+        let mut deadline = None;
+        loop {
+            if deadline.is_none() && limits.stop.is_stopped() {
+                self.send("stop")?;
+                deadline = Some(Instant::now() + STOPPING);
+            }
+            let line = match self.output.recv_timeout(POLL) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) if deadline.is_some_and(|deadline| Instant::now() >= deadline) => {
+                    return Err(ClientError::Silent("bestmove"));
+                },
+                Err(RecvTimeoutError::Timeout) => {continue;},
+                Err(RecvTimeoutError::Disconnected) => {return Err(ClientError::Gone);}
+            };
+
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words.as_slice() {
+                ["info", rest @ ..] => if let Some(parsed) = parse_info(game, rest) {
+                    info(parsed);
+                },
+                ["bestmove", name, ..] => {return Ok(find_play(game, name));},
+                ["bestmove"] => {return Ok(None);},
+                _ => ()
+            }
+        }
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        let deadline = Instant::now() + QUIT;
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = self.process.try_wait() {
+                return;
+            }
+            thread::sleep(POLL);
+        }
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
@@ -0,0 +1,315 @@
+use std::{
+    io::{self, BufRead, Write},
+    str::FromStr,
+    sync::{Arc, Mutex},
+    thread::{self, JoinHandle},
+    time::Duration
+};
+use crate::{
+    board::STANDARD_BOARD,
+    engine::{plays, Play, search::{search_with, Options, Search, MATE, MATE_BOUND}, table::Table, time::{Limits, Stop}},
+    fen::from_variant_fen,
+    game::{Game, side},
+    variant::{self, Standard, Variant, VARIANTS}
+};
+
+pub mod client;
+
+
+// the Universal Chess Interface, so the computer player can be run from chess GUIs and tournament managers
+// like cutechess-cli or Arena. the GUI keeps track of the game and sends all of it before every search,
+// and the moves it sends are checked against the rules like any other.
+
+// where replies go: stdout, or somewhere a test can read them back from.
+pub type Out = Arc<Mutex<dyn Write + Send>>;
+
+fn say(out: &Out, line: &str) {
+    let mut out = out.lock().unwrap();
+    let _ = writeln!(out, "{}", line);
+    let _ = out.flush();
+}
+
+// what UCI calls normal chess, in UCI_Variant.
+pub const CHESS: &str = "chess";
+
+pub fn variant_name(variant: &dyn Variant) -> String {
+    match variant.name() {
+        name if name == Standard.name() => CHESS.to_string(),
+        name => name.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase()
+    }
+}
+
+fn find_variant(name: &str) -> Option<&'static dyn Variant> {
+    match name.eq_ignore_ascii_case(CHESS) {
+        true => Some(&Standard),
+        false => variant::find(name)
+    }
+}
+
+// a move written the way UCI writes them, like "e2e4" or "e7e8q", if it's one the side to move has.
+pub fn find_play(game: &Game, name: &str) -> Option<Play> {
+    plays(game).into_iter().find(|play| play.name(game).eq_ignore_ascii_case(name))
+}
+
+// the moves in a line, each named on the board it's played on.
+pub fn line(game: &Game, pv: &[Play]) -> Vec<String> {
+    let mut game = game.clone();
+    let mut names = Vec::new();
+    for play in pv {
+        names.push(play.name(&game));
+        if play.play(&mut game).is_err() {
+            break;
+        }
+    }
+    names
+}
+
+pub fn info(game: &Game, search: &Search, table: &Table) -> String {
+    // a mate is given in moves rather than plies, and negative when it's the engine getting mated.
+    let score = match search.score {
+        s if s.abs() > MATE_BOUND => format!("mate {}", s.signum() * (MATE - s.abs() + 1) / 2),
+        s => format!("cp {}", s)
+    };
+    format!("info depth {} seldepth {} score {} nodes {} nps {} hashfull {} time {} pv {}",
+        search.depth, search.seldepth, score, search.nodes, search.nodes_per_second(), table.fill(),
+        search.time.as_millis(), line(game, &search.pv).join(" "))
+}
+
+// the move to play, and the reply it expects, which the GUI can have it ponder on.
+pub fn bestmove(game: &Game, search: &Search) -> String {
+    match line(game, &search.pv).as_slice() {
+        [] => "bestmove 0000".to_string(),
+        [play] => format!("bestmove {}", play),
+        [play, reply, ..] => format!("bestmove {} ponder {}", play, reply)
+    }
+}
+
+fn number<T: FromStr>(word: Option<&&str>) -> Option<T> {
+    word.and_then(|word| word.parse().ok())
+}
+
+// some GUIs send a negative time left once a side has overstepped it.
+fn millis(word: Option<&&str>) -> Option<Duration> {
+    number::<i64>(word).map(|ms| Duration::from_millis(ms.max(0) as u64))
+}
+
+pub struct Engine {
+    out: Out,
+    variant: &'static dyn Variant,
+    game: Game,
+    table: Arc<Mutex<Table>>,
+    options: Options,
+    // the search going on in the background, if there is one, and whether it was told to go on forever.
+    searching: Option<(Stop, JoinHandle<()>, bool)>
+}
+
+impl Engine {
+
+    pub fn new(out: Out) -> Engine {
+        Engine {
+            out,
+            variant: &Standard,
+            game: Game::new(&Standard, STANDARD_BOARD, None),
+            table: Arc::new(Mutex::new(Table::new(Table::DEFAULT_MB))),
+            options: Options::default(),
+            searching: None
+        }
+    }
+
+    fn start(&self) -> Game {
+        Game::new(self.variant, self.variant.start().unwrap_or(STANDARD_BOARD), None)
+    }
+
+    fn complain(&self, message: &str) {
+        say(&self.out, &format!("info string {}", message));
+    }
+
+    // handles a line from the GUI, giving back false once it's been told to quit.
+    pub fn command(&mut self, line: &str) -> bool {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.first().copied() {
+            Some("uci") => self.identify(),
+            Some("isready") => say(&self.out, "readyok"),
+            Some("ucinewgame") => {
+                self.stop();
+                self.table.lock().unwrap().clear();
+                self.game = self.start();
+            },
+            // the table can't be changed under a search that's using it.
+            Some("setoption") => {
+                self.stop();
+                self.set_option(&words[1..]);
+            },
+            Some("position") => {
+                self.stop();
+                self.position(&words[1..]);
+            },
+            Some("go") => {
+                self.stop();
+                self.go(&words[1..]);
+            },
+            Some("stop") => self.stop(),
+            Some("ponderhit") => if let Some((stop, _, _)) = &self.searching {
+                stop.ponder_hit();
+            },
+            Some("quit") => {
+                self.stop();
+                return false;
+            },
+            // anything else, "debug" included, is ignored like the protocol asks.
+            _ => ()
+        }
+        true
+    }
+
+    // stops the search going on, once it's said what it would play.
+    pub fn stop(&mut self) {
+        if let Some((stop, searching, _)) = self.searching.take() {
+            stop.stop();
+            let _ = searching.join();
+        }
+    }
+
+    // lets the search going on finish by itself, unless it never would.
+    pub fn wait(&mut self) {
+        if let Some((stop, searching, infinite)) = self.searching.take() {
+            if infinite || stop.is_pondering() {
+                stop.stop();
+            }
+            let _ = searching.join();
+        }
+    }
+
+    fn identify(&self) {
+        say(&self.out, &format!("id name {} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")));
+        say(&self.out, "id author the chess contributors");
+        say(&self.out, &format!("option name Hash type spin default {} min 1 max 65536", Table::DEFAULT_MB));
+        say(&self.out, "option name Clear Hash type button");
+        say(&self.out, "option name Ponder type check default false");
+        let variants: Vec<String> = VARIANTS.iter().map(|variant| format!("var {}", variant_name(*variant))).collect();
+        say(&self.out, &format!("option name UCI_Variant type combo default {} {}", CHESS, variants.join(" ")));
+        for name in Options::NAMES {
+            say(&self.out, &format!("option name {} type check default true", name));
+        }
+        say(&self.out, "uciok");
+    }
+
+    // "setoption name <name> [value <value>]", where either can have spaces in it.
+    fn set_option(&mut self, words: &[&str]) {
+        let at = |key: &str| words.iter().position(|word| *word == key);
+        let (name, value) = match (at("name"), at("value")) {
+            (Some(n), Some(v)) if v > n => (words[n + 1..v].join(" "), words[v + 1..].join(" ")),
+            (Some(n), _) => (words[n + 1..].join(" "), String::new()),
+            _ => {return;}
+        };
+
+        match name.to_lowercase().as_str() {
+            "hash" => match value.parse::<usize>() {
+                Ok(mb) if mb > 0 => *self.table.lock().unwrap() = Table::new(mb),
+                _ => self.complain("the hash size has to be a number of megabytes")
+            },
+            "clear hash" => self.table.lock().unwrap().clear(),
+            // whether the GUI will have it ponder, which is up to the GUI.
+            "ponder" => (),
+            "uci_variant" => match find_variant(&value) {
+                Some(variant) => {
+                    self.variant = variant;
+                    self.game = self.start();
+                },
+                None => self.complain(&format!("there's no variant called {}", value))
+            },
+            option => match value.parse::<bool>() {
+                Ok(on) if self.options.set(option, on) => (),
+                _ => self.complain(&format!("there's no option called {}", name))
+            }
+        }
+    }
+
+    // "position startpos|fen <fen> [moves <move>...]". a move that isn't legal leaves the game at the one before it.
+    fn position(&mut self, words: &[&str]) {
+        let moves = words.iter().position(|word| *word == "moves").unwrap_or(words.len());
+        let mut game = match words.first().copied() {
+            Some("startpos") => self.start(),
+            Some("fen") => match from_variant_fen(self.variant, &words[1..moves].join(" ")) {
+                Ok(game) => game,
+                Err(e) => {
+                    self.complain(&format!("couldn't read that position: {}", e));
+                    return;
+                }
+            },
+            _ => {return;}
+        };
+
+        for name in words.iter().skip(moves + 1) {
+            match find_play(&game, name).map(|play| play.play(&mut game)) {
+                Some(Ok(())) => (),
+                _ => {
+                    self.complain(&format!("{} isn't a legal move", name));
+                    break;
+                }
+            }
+        }
+        self.game = game;
+    }
+
+    // "go" with any of the limits UCI has, searching in the background until it's done or stopped.
+    fn go(&mut self, words: &[&str]) {
+        let mut limits = Limits::default();
+        let mut infinite = false;
+
+        let mut rest = words.iter().peekable();
+        while let Some(word) = rest.next() {
+            match *word {
+                "depth" => limits.depth = number(rest.next()),
+                "nodes" => limits.nodes = number(rest.next()),
+                // a mate in that many moves is found by looking that many moves deep, less the reply to the last one.
+                "mate" => limits.depth = number::<u32>(rest.next()).map(|moves| (moves * 2).saturating_sub(1).max(1)),
+                "movetime" => limits.movetime = millis(rest.next()),
+                "wtime" => limits.time[side(true)] = millis(rest.next()),
+                "btime" => limits.time[side(false)] = millis(rest.next()),
+                "winc" => limits.increment[side(true)] = millis(rest.next()).unwrap_or_default(),
+                "binc" => limits.increment[side(false)] = millis(rest.next()).unwrap_or_default(),
+                "movestogo" => limits.moves_to_go = number(rest.next()),
+                "infinite" => infinite = true,
+                "ponder" => limits.stop.ponder(),
+                // the moves to choose between, which go on until something that isn't one.
+                "searchmoves" => while let Some(play) = rest.peek().and_then(|name| find_play(&self.game, name)) {
+                    limits.moves.push(play);
+                    rest.next();
+                },
+                _ => ()
+            }
+        }
+
+        let game = self.game.clone();
+        let table = self.table.clone();
+        let options = self.options;
+        let out = self.out.clone();
+        let stop = limits.stop.clone();
+
+        let searching = thread::spawn(move || {
+            let search = search_with(&game, &limits, &mut table.lock().unwrap(), options, |search, table| {
+                say(&out, &info(&game, search, table));
+            });
+            // searching forever, or on the opponent's time, it doesn't give its move until it's asked for it.
+            while !limits.stop.is_stopped() && (infinite || limits.stop.is_pondering()) {
+                thread::sleep(Duration::from_millis(5));
+            }
+            say(&out, &bestmove(&game, &search));
+        });
+        self.searching = Some((stop, searching, infinite));
+    }
+}
+
+// talks to the GUI on stdin and stdout until it says to quit. if it hangs up instead, a search with limits
+// is left to finish first, so a list of commands can be piped in.
+pub fn run() -> io::Result<()> {
+    let mut engine = Engine::new(Arc::new(Mutex::new(io::stdout())));
+    for line in io::stdin().lock().lines() {
+        if !engine.command(&line?) {
+            return Ok(());
+        }
+    }
+    engine.wait();
+    Ok(())
+}
@@ -0,0 +1,127 @@
+use std::fmt;
+use crate::{
+    board::{Board, Loc, MAX_SIZE, read_board},
+    check::{get_king, is_check},
+    fen::square_name,
+    game::Game,
+    piece::move_list,
+    types::{Movement::*, Space::*}
+};
+
+
+// the lines a check can come along, as the umpire describes them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckLine {
+    Rank,
+    File,
+    LongDiagonal,
+    ShortDiagonal,
+    Knight
+}
+
+// what the umpire says out loud, which both players hear.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Announcement {
+    Illegal,
+    Capture(String),
+    Check(CheckLine),
+    // how many pawn captures the side to move could try.
+    PawnTries(usize)
+}
+
+impl fmt::Display for Announcement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Announcement::Illegal => write!(f, "Illegal."),
+            Announcement::Capture(square) => write!(f, "Capture on {}.", square),
+            Announcement::Check(line) => write!(f, "Check {}.", match line {
+                CheckLine::Rank => "on the rank",
+                CheckLine::File => "on the file",
+                CheckLine::LongDiagonal => "on the long diagonal",
+                CheckLine::ShortDiagonal => "on the short diagonal",
+                CheckLine::Knight => "by a knight"
+            }),
+            Announcement::PawnTries(1) => write!(f, "1 pawn try."),
+            Announcement::PawnTries(n) => write!(f, "{} pawn tries.", n)
+        }
+    }
+}
+
+// how many squares long the diagonal through 'loc' heading along (1, 'dy') is.
+fn diagonal_length(board: &Board, loc: Loc, dy: isize) -> usize {
+    let reach = MAX_SIZE as isize;
+    (-reach..=reach).filter(|n| board.contains([loc[0] + n, loc[1] + n * dy])).count()
+}
+
+// which way the piece at 'fromc' is checking the king at 'kingc' from.
+fn check_line(board: &Board, fromc: Loc, kingc: Loc) -> CheckLine {
+    let (dx, dy) = (kingc[0] - fromc[0], kingc[1] - fromc[1]);
+    if dy == 0 {
+        CheckLine::Rank
+    } else if dx == 0 {
+        CheckLine::File
+    } else if dx.abs() != dy.abs() {
+        CheckLine::Knight
+    } else {
+        // on a square board a square's two diagonals are never the same length.
+        let along = diagonal_length(board, kingc, dx.signum() * dy.signum());
+        let across = diagonal_length(board, kingc, -dx.signum() * dy.signum());
+        match along > across {
+            true => CheckLine::LongDiagonal,
+            false => CheckLine::ShortDiagonal
+        }
+    }
+}
+
+// every piece giving check to the king of the given team.
+fn checkers(board: &Board, is_white: bool) -> Vec<Loc> {
+    let kingc = match get_king(board, is_white) {
+        Some(kingc) if is_check(board, kingc, is_white) => kingc,
+        _ => {return Vec::new();}
+    };
+
+    let mut checkers = Vec::new();
+    for y in 1..=board.height {
+        for x in 1..=board.width {
+            if let Some(piece) = read_board(board, [x, y]) {
+                if piece != Open && piece.is_white() != is_white
+                    && move_list(board, [x, y], piece).iter().any(|movement| movement.to == kingc && movement.relation == Enemy) {
+                    checkers.push([x, y]);
+                }
+            }
+        }
+    }
+    checkers
+}
+
+// everything the umpire says once a move has gone through.
+pub fn announce(game: &Game) -> Vec<Announcement> {
+    let board = &game.board;
+    let mut said = Vec::new();
+
+    if let Some(loc) = game.last_capture {
+        said.push(Announcement::Capture(square_name(board.height, loc)));
+    }
+
+    if let Some(kingc) = get_king(board, game.turn_white) {
+        for fromc in checkers(board, game.turn_white) {
+            said.push(Announcement::Check(check_line(board, fromc, kingc)));
+        }
+    }
+
+    let mut tries = 0;
+    for y in 1..=board.height {
+        for x in 1..=board.width {
+            if let Some(Pawn(w, _)) = read_board(board, [x, y]) {
+                if w == game.turn_white {
+                    tries += game.variant.moves(board, [x, y]).iter()
+                        .filter(|movement| matches!(movement.relation, Enemy | EnPessant)).count();
+                }
+            }
+        }
+    }
+    if tries > 0 {
+        said.push(Announcement::PawnTries(tries));
+    }
+    said
+}
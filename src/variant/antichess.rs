@@ -0,0 +1,87 @@
+use crate::{
+    board::{Board, Loc, read_board},
+    check::has_legal_move,
+    game::{Game, Outcome},
+    piece::move_list,
+    types::{IllegalMove, MoveData, Movement::*, Promotion, Space::*}
+};
+use super::Variant;
+
+
+// losing chess: taking is compulsory, the king is just another piece,
+// and the first side to run out of pieces or moves wins.
+pub struct Antichess;
+
+// whether the team has any capture at all, which rules out every other move.
+fn can_capture(board: &Board, is_white: bool) -> bool {
+    for y in 1..=board.height {
+        for x in 1..=board.width {
+            match read_board(board, [x, y]) {
+                Some(Open) | None => {},
+                Some(piece) => if piece.is_white() == is_white
+                    && move_list(board, [x, y], piece).iter().any(|movement| matches!(movement.relation, Enemy | EnPessant)) {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+impl Variant for Antichess {
+    fn name(&self) -> &'static str {
+        "Antichess"
+    }
+
+    // any number of kings, including none.
+    fn valid(&self, _board: &Board) -> bool {
+        true
+    }
+
+    // there's no check to worry about, so this skips deep_checks altogether.
+    // moves that would pass up a capture are marked Check, which check_reason explains.
+    fn moves(&self, board: &Board, fromc: Loc) -> Vec<MoveData> {
+        let from = match read_board(board, fromc) {
+            Some(Open) | None => {return Vec::new();},
+            Some(piece) => piece
+        };
+
+        let must_capture = can_capture(board, from.is_white());
+        let mut moves = move_list(board, fromc, from);
+        moves.retain(|movement| !matches!(movement.relation, KingSide | QueenSide));
+
+        for movement in moves.iter_mut() {
+            if must_capture && !matches!(movement.relation, Enemy | EnPessant | Blocked) {
+                movement.relation = Check;
+            }
+        }
+        moves
+    }
+
+    fn check_reason(&self, _board: &Board, _fromc: Loc, _toc: Loc) -> IllegalMove {
+        IllegalMove::MustCapture
+    }
+
+    fn promotions(&self) -> &'static [Promotion] {
+        &[Promotion::Queen, Promotion::Rook, Promotion::Bishop, Promotion::Knight, Promotion::King]
+    }
+
+    fn castles(&self) -> bool {
+        false
+    }
+
+    fn in_check(&self, _board: &Board, _is_white: bool) -> bool {
+        false
+    }
+
+    fn outcome(&self, game: &Game) -> Option<Outcome> {
+        let turn_white = game.turn_white;
+        if !game.board.spaces().any(|space| space != Open && space.is_white() == turn_white) {
+            Some(Outcome::OutOfPieces(turn_white))
+        } else if !has_legal_move(self, &game.board, turn_white) {
+            Some(Outcome::OutOfMoves(turn_white))
+        } else {
+            None
+        }
+    }
+}
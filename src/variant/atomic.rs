@@ -0,0 +1,112 @@
+use crate::{
+    board::{Board, Loc, do_move, read_board, write_board},
+    check::{self, get_king},
+    game::{Game, Outcome},
+    piece::move_list,
+    types::{Direction, IllegalMove, MoveData, Movement, Movement::*, Promotion, Space, Space::*}
+};
+use super::{Variant, promote, standard_outcome};
+
+
+// captures blow up the capturing piece and everything around it apart from pawns,
+// and blowing up the enemy king wins.
+pub struct Atomic;
+
+// clears the square a capture landed on, and every piece next to it that isn't a pawn.
+pub fn explode(board: &mut Board, toc: Loc) {
+    write_board(board, toc, Open);
+    for dir in Direction::CARDINALS.into_iter().chain(Direction::ORDINALS) {
+        let loc = dir.translate(toc, 1);
+        match read_board(board, loc) {
+            Some(Pawn(_, _)) | Some(Open) | None => {},
+            Some(_) => {write_board(board, loc, Open);}
+        }
+    }
+}
+
+// kings next to each other can't be in check, since taking one would blow up the other.
+fn kings_touch(board: &Board) -> bool {
+    match (get_king(board, true), get_king(board, false)) {
+        (Some(white), Some(black)) => (white[0] - black[0]).abs() <= 1 && (white[1] - black[1]).abs() <= 1,
+        _ => false
+    }
+}
+
+// the board once a move has been played out, explosion and all.
+fn played(board: &Board, fromc: Loc, from: Space, movement: MoveData) -> Board {
+    let mut test_board = *board;
+    do_move(&mut test_board, fromc, from, movement.to, movement.relation);
+    Atomic.after_move(&mut test_board, fromc, movement.to, movement.relation, Promotion::Queen);
+    test_board
+}
+
+impl Variant for Atomic {
+    fn name(&self) -> &'static str {
+        "Atomic"
+    }
+
+    // a move is fine as long as your own king survives it,
+    // and either the enemy king doesn't or yours isn't left in check.
+    fn moves(&self, board: &Board, fromc: Loc) -> Vec<MoveData> {
+        let from = match read_board(board, fromc) {
+            Some(Open) | None => {return Vec::new();},
+            Some(piece) => piece
+        };
+        let is_white = from.is_white();
+
+        let mut moves = move_list(board, fromc, from);
+        for movement in moves.iter_mut() {
+            if movement.relation == Blocked {
+                continue;
+            }
+
+            let test_board = played(board, fromc, from, *movement);
+            let legal = match (get_king(&test_board, is_white), get_king(&test_board, !is_white)) {
+                (None, _) => false,
+                (Some(_), None) => true,
+                (Some(_), Some(_)) => !self.in_check(&test_board, is_white)
+            };
+            if !legal {
+                movement.relation = Check;
+            }
+        }
+        moves
+    }
+
+    fn check_reason(&self, board: &Board, fromc: Loc, toc: Loc) -> IllegalMove {
+        let from = match read_board(board, fromc) {
+            Some(Open) | None => {return IllegalMove::NoPiece;},
+            Some(piece) => piece
+        };
+
+        let movement = move_list(board, fromc, from).into_iter().find(|movement| movement.to == toc);
+        if let Some(movement) = movement {
+            if get_king(&played(board, fromc, from, movement), from.is_white()).is_none() {
+                return IllegalMove::ExplodesOwnKing;
+            }
+        }
+        check::check_reason(board, fromc, from)
+    }
+
+    fn after_move(&self, board: &mut Board, _fromc: Loc, toc: Loc, relation: Movement, promotion: Promotion) {
+        match relation {
+            Enemy | EnPessant => explode(board, toc),
+            _ => promote(board, toc, promotion)
+        }
+    }
+
+    fn in_check(&self, board: &Board, is_white: bool) -> bool {
+        !kings_touch(board) && check::in_check(board, is_white)
+    }
+
+    fn outcome(&self, game: &Game) -> Option<Outcome> {
+        match get_king(&game.board, game.turn_white) {
+            None => Some(Outcome::Exploded(!game.turn_white)),
+            Some(_) => standard_outcome(self, game)
+        }
+    }
+
+    fn explodes(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,58 @@
+use crate::{
+    board::{Board, do_move, read_board},
+    check::{has_legal_move, in_check},
+    game::{Game, Outcome, side},
+    pocket::can_drop,
+    types::{Movement::Drop, Space::*}
+};
+use super::Variant;
+
+
+// Crazyhouse played by two teams over two boards, where whatever you take goes to your partner.
+// the boards themselves are tied together by the bughouse module.
+pub struct Bughouse;
+
+// whether any piece dropped in the way would stop the check.
+// in Bughouse that piece could still turn up from the other board, so it isn't mate yet.
+fn could_block(board: &Board, is_white: bool) -> bool {
+    for y in 1..=board.height {
+        for x in 1..=board.width {
+            if read_board(board, [x, y]) != Some(Open) {
+                continue;
+            }
+            let mut test_board = *board;
+            do_move(&mut test_board, [x, y], Knight(is_white), [x, y], Drop);
+            if !in_check(&test_board, is_white) {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+impl Variant for Bughouse {
+    fn name(&self) -> &'static str {
+        "Bughouse"
+    }
+
+    // a side with nothing to play has to wait for a piece to arrive or for their clock to run out,
+    // so there's no stalemate, and only a check nothing could block is mate.
+    fn outcome(&self, game: &Game) -> Option<Outcome> {
+        let turn_white = game.turn_white;
+        if has_legal_move(self, &game.board, turn_white) || can_drop(&game.board, &game.pockets[side(turn_white)], turn_white) {
+            return None;
+        }
+        match in_check(&game.board, turn_white) && !could_block(&game.board, turn_white) {
+            true => Some(Outcome::Checkmate(!turn_white)),
+            false => None
+        }
+    }
+
+    fn has_pockets(&self) -> bool {
+        true
+    }
+
+    fn passes_captures(&self) -> bool {
+        true
+    }
+}
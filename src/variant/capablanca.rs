@@ -0,0 +1,29 @@
+use crate::{
+    board::Board,
+    fen::from_variant_fen,
+    types::Promotion
+};
+use super::Variant;
+
+
+// chess on a 10x8 board, with an archbishop and a chancellor squeezed in next to the bishops.
+// castling takes the king three squares either way, to the c or i file.
+pub struct Capablanca;
+
+impl Capablanca {
+    pub const START: &'static str = "rnabqkbcnr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNABQKBCNR w KQkq - 0 1";
+}
+
+impl Variant for Capablanca {
+    fn name(&self) -> &'static str {
+        "Capablanca"
+    }
+
+    fn start(&self) -> Option<Board> {
+        from_variant_fen(&Capablanca, Capablanca::START).ok().map(|game| game.board)
+    }
+
+    fn promotions(&self) -> &'static [Promotion] {
+        &[Promotion::Queen, Promotion::Chancellor, Promotion::Archbishop, Promotion::Rook, Promotion::Bishop, Promotion::Knight]
+    }
+}
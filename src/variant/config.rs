@@ -0,0 +1,166 @@
+use std::{fmt, fs, sync::OnceLock};
+use crate::{
+    betza::{self, BetzaError, FairyPiece},
+    board::Board,
+    fen::{from_variant_fen, FenError},
+    types::Promotion
+};
+use super::Variant;
+
+
+// a variant written down in a file instead of in code, so new ones can be tried out without building anything.
+// each line is "key = value", and anything after a '#' is ignored:
+//
+//   name = Cannon chess
+//   start = rnbqkbnr/pppppppp/1x4x1/8/8/1X4X1/PPPPPPPP/RNBQKBNR w KQkq - 0 1
+//   piece = X mRcpR
+//   promotions = QRBN
+//   castling = yes
+//
+// every piece line adds a piece with that letter, moving the way the Betza notation after it says.
+// the rest is standard chess; made up pieces don't promote, and pawns can only become the usual pieces.
+pub struct ConfigVariant {
+    name: &'static str,
+    // worked out once the variant is in place, since reading the FEN needs the variant's pieces.
+    start: OnceLock<Board>,
+    pieces: Vec<(char, u8)>,
+    promotions: &'static [Promotion],
+    castles: bool
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(String),
+    // a line that isn't "key = value", or has a key that doesn't mean anything.
+    Line(usize),
+    Name,
+    Start(FenError),
+    Piece(usize, BetzaError),
+    // a piece letter that's already a built in piece's, or another line's.
+    Letter(usize, char),
+    // more made up pieces than there's room for, counting every other variant's.
+    Pieces(BetzaError),
+    Promotions(usize),
+    Castling(usize)
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(e) => write!(f, "couldn't read the file: {}", e),
+            ConfigError::Line(n) => write!(f, "line {} should look like 'key = value'", n),
+            ConfigError::Name => write!(f, "the variant needs a name"),
+            ConfigError::Start(e) => write!(f, "the start position doesn't work: {}", e),
+            ConfigError::Piece(n, e) => write!(f, "the piece on line {} doesn't work: {}", n, e),
+            ConfigError::Letter(n, c) => write!(f, "line {} uses the letter {}, which is already taken", n, c),
+            ConfigError::Pieces(e) => write!(f, "the pieces can't be added: {}", e),
+            ConfigError::Promotions(n) => write!(f, "line {} should list what pawns promote to, like 'QRBN'", n),
+            ConfigError::Castling(n) => write!(f, "line {} should say 'yes' or 'no'", n)
+        }
+    }
+}
+
+impl Variant for ConfigVariant {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn start(&self) -> Option<Board> {
+        self.start.get().copied()
+    }
+
+    fn promotions(&self) -> &'static [Promotion] {
+        self.promotions
+    }
+
+    fn castles(&self) -> bool {
+        self.castles
+    }
+
+    fn fairy(&self, letter: char) -> Option<u8> {
+        self.pieces.iter().find(|(c, _)| *c == letter).map(|(_, index)| *index)
+    }
+}
+
+// the letters the normal pieces go by.
+const BUILT_IN: [char; 8] = ['P', 'N', 'B', 'R', 'Q', 'K', 'A', 'C'];
+
+pub fn load(path: &str) -> Result<&'static dyn Variant, ConfigError> {
+    let text = fs::read_to_string(path).map_err(|e| ConfigError::Read(e.to_string()))?;
+    parse(&text)
+}
+
+// the variant lives for the rest of the program, like the built in ones.
+// its pieces are only added once everything else checks out, and taken back out if the start position doesn't.
+pub fn parse(text: &str) -> Result<&'static dyn Variant, ConfigError> {
+
+    let mut name = None;
+    let mut start = None;
+    let mut pieces: Vec<FairyPiece> = Vec::new();
+    let mut promotions: &'static [Promotion] = &[Promotion::Queen, Promotion::Rook, Promotion::Bishop, Promotion::Knight];
+    let mut castles = true;
+
+    for (i, line) in text.lines().enumerate() {
+        let n = i + 1;
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let (key, value) = line.split_once('=').ok_or(ConfigError::Line(n))?;
+        let value = value.trim();
+        match key.trim() {
+            "name" => name = Some(value.to_string()),
+            "start" => start = Some(value.to_string()),
+            "piece" => {
+                let (letter, notation) = value.split_once(char::is_whitespace).ok_or(ConfigError::Line(n))?;
+                let letter = match letter.chars().collect::<Vec<char>>()[..] {
+                    [letter] if letter.is_ascii_alphabetic() => letter.to_ascii_uppercase(),
+                    _ => {return Err(ConfigError::Line(n));}
+                };
+                // a FEN reads the built in letters first, so a piece using one could never be put on the board.
+                if BUILT_IN.contains(&letter) || pieces.iter().any(|piece| piece.letter == letter) {
+                    return Err(ConfigError::Letter(n, letter));
+                }
+                pieces.push(FairyPiece::new(letter, notation).map_err(|e| ConfigError::Piece(n, e))?);
+            },
+            "promotions" => {
+                // staying a pawn is only a choice where promoting is optional, which a config can't ask for.
+                let list = value.chars().map(Promotion::from_letter).collect::<Option<Vec<Promotion>>>()
+                    .filter(|list| !list.is_empty() && !list.contains(&Promotion::Pawn)).ok_or(ConfigError::Promotions(n))?;
+                promotions = Box::leak(list.into_boxed_slice());
+            },
+            "castling" => castles = match value {
+                "yes" => true,
+                "no" => false,
+                _ => {return Err(ConfigError::Castling(n));}
+            },
+            _ => {return Err(ConfigError::Line(n));}
+        }
+    }
+
+    let name = name.filter(|name| !name.is_empty()).ok_or(ConfigError::Name)?;
+    let letters: Vec<char> = pieces.iter().map(|piece| piece.letter).collect();
+    let indices = betza::register(pieces).map_err(ConfigError::Pieces)?;
+    let variant: &'static ConfigVariant = Box::leak(Box::new(ConfigVariant {
+        name: Box::leak(name.into_boxed_str()),
+        start: OnceLock::new(),
+        pieces: letters.into_iter().zip(indices.iter().copied()).collect(),
+        promotions,
+        castles
+    }));
+
+    // without a start position of its own, it starts the way standard chess does.
+    if let Some(fen) = start {
+        match from_variant_fen(variant, &fen) {
+            Ok(game) => {
+                let _ = variant.start.set(game.board);
+            },
+            Err(e) => {
+                betza::unregister(&indices);
+                return Err(ConfigError::Start(e));
+            }
+        }
+    }
+    Ok(variant)
+}
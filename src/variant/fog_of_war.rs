@@ -0,0 +1,66 @@
+use crate::{
+    board::{Board, Loc, MAX_SIZE, read_board},
+    check::get_king,
+    game::{Game, Outcome},
+    piece::move_list,
+    types::{MoveData, Movement::*, Space::*}
+};
+use super::{Variant, standard_outcome};
+
+
+// dark chess: each side only sees the squares its own pieces could move to.
+// nobody is told about check, so the king can walk into it and be taken like anything else.
+pub struct FogOfWar;
+
+// the squares the given team can see: its own pieces and everywhere they could move.
+fn visible(board: &Board, is_white: bool) -> [[bool; MAX_SIZE]; MAX_SIZE] {
+    let mut seen = [[false; MAX_SIZE]; MAX_SIZE];
+    for y in 1..=board.height {
+        for x in 1..=board.width {
+            let piece = match read_board(board, [x, y]) {
+                Some(piece) if piece != Open && piece.is_white() == is_white => piece,
+                _ => {continue;}
+            };
+            seen[y as usize - 1][x as usize - 1] = true;
+            for movement in move_list(board, [x, y], piece) {
+                if movement.relation != Blocked {
+                    seen[movement.to[1] as usize - 1][movement.to[0] as usize - 1] = true;
+                }
+            }
+        }
+    }
+    seen
+}
+
+impl Variant for FogOfWar {
+    fn name(&self) -> &'static str {
+        "Fog of War"
+    }
+
+    // with no check there's nothing for deep_checks to rule out.
+    fn moves(&self, board: &Board, fromc: Loc) -> Vec<MoveData> {
+        match read_board(board, fromc) {
+            Some(Open) | None => Vec::new(),
+            Some(piece) => move_list(board, fromc, piece)
+        }
+    }
+
+    fn in_check(&self, _board: &Board, _is_white: bool) -> bool {
+        false
+    }
+
+    fn outcome(&self, game: &Game) -> Option<Outcome> {
+        match get_king(&game.board, game.turn_white) {
+            None => Some(Outcome::KingTaken(!game.turn_white)),
+            Some(_) => standard_outcome(self, game)
+        }
+    }
+
+    fn hides(&self) -> bool {
+        true
+    }
+
+    fn visible(&self, board: &Board, is_white: bool) -> [[bool; MAX_SIZE]; MAX_SIZE] {
+        visible(board, is_white)
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{
+    board::{Board, Loc, read_board, write_board},
+    fen::from_variant_fen,
+    types::{MoveData, Movement, Movement::*, Promotion, Space::*}
+};
+use super::{Variant, last_rank};
+
+
+// chess on a 10x10 board, with the rooks behind the rest of the pieces and the pawns a rank further up.
+// there's no castling. pawns may promote on the last three ranks and have to on the last one,
+// but only to a piece their side has already lost, so a pawn with nothing to become can't go all the way.
+pub struct Grand;
+
+impl Grand {
+    pub const START: &'static str = "r8r/1nbqkcabn1/pppppppppp/10/10/10/10/PPPPPPPPPP/1NBQKCABN1/R8R w - - 0 1";
+
+    // how many of each piece a side starts with, and so can get back.
+    const PIECES: [(Promotion, usize); 6] = [
+        (Promotion::Queen, 1), (Promotion::Chancellor, 1), (Promotion::Archbishop, 1),
+        (Promotion::Rook, 2), (Promotion::Bishop, 2), (Promotion::Knight, 2)
+    ];
+}
+
+// whether 'toc' is on one of the last three ranks for the given team.
+fn in_zone(board: &Board, toc: Loc, is_white: bool) -> bool {
+    (toc[1] - last_rank(board, is_white)).abs() < 3
+}
+
+// the pieces the given team has fewer of than it started with.
+fn captured(board: &Board, is_white: bool) -> Vec<Promotion> {
+    Grand::PIECES.into_iter().filter(|(promotion, count)| {
+        let piece = promotion.piece(is_white);
+        board.spaces().filter(|space| match (space, piece) {
+            (Rook(w, _), Rook(_, _)) => *w == is_white,
+            (space, piece) => *space == piece
+        }).count() < *count
+    }).map(|(promotion, _)| promotion).collect()
+}
+
+impl Variant for Grand {
+    fn name(&self) -> &'static str {
+        "Grand"
+    }
+
+    fn start(&self) -> Option<Board> {
+        from_variant_fen(&Grand, Grand::START).ok().map(|game| game.board)
+    }
+
+    // a pawn with nothing to promote to can't go onto the last rank.
+    fn filter(&self, board: &Board, fromc: Loc, moves: &mut Vec<MoveData>) {
+        if let Some(Pawn(w, _)) = read_board(board, fromc) {
+            if captured(board, w).is_empty() {
+                for movement in moves.iter_mut().filter(|movement| movement.to[1] == last_rank(board, w)) {
+                    movement.relation = Blocked;
+                }
+            }
+        }
+    }
+
+    fn after_move(&self, board: &mut Board, _fromc: Loc, toc: Loc, _relation: Movement, promotion: Promotion) {
+        if let Some(Pawn(w, _)) = read_board(board, toc) {
+            if in_zone(board, toc, w) {
+                write_board(board, toc, promotion.piece(w));
+            }
+        }
+    }
+
+    fn promotions(&self) -> &'static [Promotion] {
+        &[Promotion::Queen, Promotion::Chancellor, Promotion::Archbishop, Promotion::Rook, Promotion::Bishop, Promotion::Knight, Promotion::Pawn]
+    }
+
+    fn promotions_at(&self, board: &Board, fromc: Loc, toc: Loc) -> Vec<Promotion> {
+        let is_white = match read_board(board, fromc) {
+            Some(Pawn(w, _)) if in_zone(board, toc, w) => w,
+            _ => {return Vec::new();}
+        };
+        let mut promotions = captured(board, is_white);
+        if toc[1] != last_rank(board, is_white) {
+            promotions.push(Promotion::Pawn);
+        }
+        promotions
+    }
+
+    fn pawn_rank(&self) -> isize {
+        3
+    }
+
+    fn castles(&self) -> bool {
+        false
+    }
+}
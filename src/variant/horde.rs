@@ -0,0 +1,54 @@
+use crate::{
+    board::{Board, Loc, read_board, write_board},
+    fen::from_variant_fen,
+    game::{Game, Outcome},
+    types::{Movement, Movement::*, PawnState, Promotion, Space::*}
+};
+use super::{Variant, promote, standard_outcome};
+
+
+// thirty six white pawns and no white king against a normal black army.
+// black wins by taking every white piece, white by checkmating as usual.
+// pawns on the first rank can skip too, but can't be taken en passant when they do.
+pub struct Horde;
+
+impl Horde {
+    pub const START: &'static str = "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1";
+}
+
+impl Variant for Horde {
+    fn name(&self) -> &'static str {
+        "Horde"
+    }
+
+    fn start(&self) -> Option<Board> {
+        from_variant_fen(&Horde, Horde::START).ok().map(|game| game.board)
+    }
+
+    fn valid(&self, board: &Board) -> bool {
+        let kings = |is_white: bool| board.spaces().filter(|space| matches!(space, King(w, _) if *w == is_white)).count();
+        kings(true) == 0 && kings(false) == 1
+    }
+
+    fn after_move(&self, board: &mut Board, fromc: Loc, toc: Loc, relation: Movement, promotion: Promotion) {
+        if let Some(Pawn(w, _)) = read_board(board, toc) {
+            let first = if w {board.height} else {1};
+            let second = if w {board.height - 1} else {2};
+            match relation {
+                _ if fromc[1] != first => (),
+                PawnSkip => {write_board(board, toc, Pawn(w, PawnState::Moved));},
+                // stepping up from the first rank, it can still skip from the second.
+                Empty | Enemy if toc[1] == second => {write_board(board, toc, Pawn(w, PawnState::NotMoved));},
+                _ => ()
+            }
+        }
+        promote(board, toc, promotion);
+    }
+
+    fn outcome(&self, game: &Game) -> Option<Outcome> {
+        match game.board.spaces().any(|space| space != Open && space.is_white()) {
+            true => standard_outcome(self, game),
+            false => Some(Outcome::HordeTaken)
+        }
+    }
+}
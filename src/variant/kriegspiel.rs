@@ -0,0 +1,34 @@
+use crate::{
+    board::{Board, MAX_SIZE},
+    types::Space::*
+};
+use super::Variant;
+
+
+// normal chess rules, except that each side only sees its own pieces
+// and learns about the other side from what the umpire announces.
+pub struct Kriegspiel;
+
+impl Variant for Kriegspiel {
+    fn name(&self) -> &'static str {
+        "Kriegspiel"
+    }
+
+    fn visible(&self, board: &Board, is_white: bool) -> [[bool; MAX_SIZE]; MAX_SIZE] {
+        let mut seen = [[false; MAX_SIZE]; MAX_SIZE];
+        for (row, spaces) in seen.iter_mut().zip(board.squares) {
+            for (seen, space) in row.iter_mut().zip(spaces) {
+                *seen = space != Open && space.is_white() == is_white;
+            }
+        }
+        seen
+    }
+
+    fn hides(&self) -> bool {
+        true
+    }
+
+    fn umpired(&self) -> bool {
+        true
+    }
+}
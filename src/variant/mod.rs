@@ -0,0 +1,273 @@
+mod antichess;
+mod atomic;
+mod bughouse;
+mod capablanca;
+pub mod config;
+mod fog_of_war;
+mod grand;
+mod horde;
+mod kriegspiel;
+mod racing_kings;
+
+use crate::{
+    board::{Board, Loc, MAX_SIZE, read_board, write_board},
+    check::{self, deep_checks, get_king, is_checkmated, is_stalemated},
+    game::{Game, Outcome, side},
+    piece::move_list,
+    pocket::can_drop,
+    types::{IllegalMove, MoveData, Movement, Promotion, Space::*}
+};
+
+pub use antichess::Antichess;
+pub use atomic::Atomic;
+pub use bughouse::Bughouse;
+pub use capablanca::Capablanca;
+pub use fog_of_war::FogOfWar;
+pub use grand::Grand;
+pub use horde::Horde;
+pub use kriegspiel::Kriegspiel;
+pub use racing_kings::RacingKings;
+
+
+// the rules a game is played by.
+// everything defaults to standard chess, so a variant only has to write the parts it changes.
+pub trait Variant: Sync {
+
+    fn name(&self) -> &'static str;
+
+    // a start position of its own, or None to use whichever one was picked.
+    fn start(&self) -> Option<Board> {
+        None
+    }
+
+    // whether a position can be played at all; normally that means one king each.
+    fn valid(&self, board: &Board) -> bool {
+        [true, false].into_iter().all(|is_white| {
+            board.spaces().filter(|space| matches!(space, King(w, _) if *w == is_white)).count() == 1
+        })
+    }
+
+    // every move the piece at 'fromc' has, with the ones it can't make marked Check or Blocked.
+    fn moves(&self, board: &Board, fromc: Loc) -> Vec<MoveData> {
+        let from = match read_board(board, fromc) {
+            Some(Open) | None => {return Vec::new();},
+            Some(piece) => piece
+        };
+
+        let mut moves = move_list(board, fromc, from);
+        deep_checks(board, fromc, &mut moves);
+        self.filter(board, fromc, &mut moves);
+        moves
+    }
+
+    // takes away or marks moves after they've been found, without changing how pieces move.
+    fn filter(&self, _board: &Board, _fromc: Loc, _moves: &mut Vec<MoveData>) {}
+
+    // why the move from 'fromc' to 'toc' was marked Check.
+    fn check_reason(&self, board: &Board, fromc: Loc, _toc: Loc) -> IllegalMove {
+        match read_board(board, fromc) {
+            Some(from) => check::check_reason(board, fromc, from),
+            None => IllegalMove::NoPiece
+        }
+    }
+
+    // anything else that happens to the board once a move has been made.
+    fn after_move(&self, board: &mut Board, _fromc: Loc, toc: Loc, _relation: Movement, promotion: Promotion) {
+        promote(board, toc, promotion);
+    }
+
+    // the pieces a pawn may become, the first being what it becomes if nobody says.
+    fn promotions(&self) -> &'static [Promotion] {
+        &[Promotion::Queen, Promotion::Rook, Promotion::Bishop, Promotion::Knight]
+    }
+
+    // the ones a pawn moving from 'fromc' to 'toc' can pick from right now, or none if the move doesn't promote.
+    fn promotions_at(&self, board: &Board, fromc: Loc, toc: Loc) -> Vec<Promotion> {
+        match promotes(board, fromc, toc) {
+            true => self.promotions().to_vec(),
+            false => Vec::new()
+        }
+    }
+
+    // the made up piece a letter in a FEN stands for, if the variant has any.
+    fn fairy(&self, _letter: char) -> Option<u8> {
+        None
+    }
+
+    // how many ranks in from its own side a pawn starts, and so can still skip forward from.
+    fn pawn_rank(&self) -> isize {
+        2
+    }
+
+    // whether kings and rooks can castle at all.
+    fn castles(&self) -> bool {
+        true
+    }
+
+    // whether the king of the given team is in check, as far as these rules are concerned.
+    fn in_check(&self, board: &Board, is_white: bool) -> bool {
+        check::in_check(board, is_white)
+    }
+
+    // checked after every move, when 'game.turn_white' is the side about to play.
+    fn outcome(&self, game: &Game) -> Option<Outcome> {
+        standard_outcome(self, game)
+    }
+
+    // whether the checks each side has given are part of the game, and so shown and written into the FEN.
+    fn counts_checks(&self) -> bool {
+        false
+    }
+
+    // whether captured pieces go into a pocket to be dropped back in later.
+    fn has_pockets(&self) -> bool {
+        false
+    }
+
+    // whether captured pieces go to the partner on the other board instead of the side that took them.
+    fn passes_captures(&self) -> bool {
+        false
+    }
+
+    // whether captures blow up the squares around them, which the board shows.
+    fn explodes(&self) -> bool {
+        false
+    }
+
+    // whether each side only sees part of the board, which hot seat play has to hide between turns.
+    fn hides(&self) -> bool {
+        false
+    }
+
+    // the squares the given team can see, indexed the same way as the board.
+    fn visible(&self, _board: &Board, _is_white: bool) -> [[bool; MAX_SIZE]; MAX_SIZE] {
+        [[true; MAX_SIZE]; MAX_SIZE]
+    }
+
+    // whether an umpire tells the players about captures, checks and illegal tries.
+    fn umpired(&self) -> bool {
+        false
+    }
+}
+
+// checkmate and stalemate, for variants that add to them rather than replace them.
+pub fn standard_outcome<V: Variant + ?Sized>(variant: &V, game: &Game) -> Option<Outcome> {
+    if is_checkmated(variant, &game.board, game.turn_white) {
+        Some(Outcome::Checkmate(!game.turn_white))
+    } else if is_stalemated(variant, &game.board, game.turn_white) {
+        Some(Outcome::Stalemate)
+    } else {
+        None
+    }
+}
+
+// the rank at the far end for the given team, where its pawns promote.
+pub fn last_rank(board: &Board, is_white: bool) -> isize {
+    if is_white {1} else {board.height}
+}
+
+// true if a pawn moving to 'toc' has reached the far end.
+pub fn promotes(board: &Board, fromc: Loc, toc: Loc) -> bool {
+    match read_board(board, fromc) {
+        Some(Pawn(w, _)) => toc[1] == last_rank(board, w),
+        _ => false
+    }
+}
+
+// a pawn that reaches the far end becomes whatever was picked.
+pub fn promote(board: &mut Board, toc: Loc, promotion: Promotion) {
+    if let Some(Pawn(w, _)) = read_board(board, toc) {
+        if toc[1] == last_rank(board, w) {
+            write_board(board, toc, promotion.piece(w));
+        }
+    }
+}
+
+
+pub struct Standard;
+
+impl Variant for Standard {
+    fn name(&self) -> &'static str {
+        "Standard"
+    }
+}
+
+
+// getting your king to one of the four centre squares wins.
+pub struct KingOfTheHill;
+
+impl KingOfTheHill {
+    pub const HILL: [Loc; 4] = [[4, 4], [5, 4], [4, 5], [5, 5]];
+}
+
+impl Variant for KingOfTheHill {
+    fn name(&self) -> &'static str {
+        "King of the Hill"
+    }
+
+    fn outcome(&self, game: &Game) -> Option<Outcome> {
+        let mover = !game.turn_white;
+        match get_king(&game.board, mover) {
+            Some(kingc) if KingOfTheHill::HILL.contains(&kingc) => Some(Outcome::Hill(mover)),
+            _ => standard_outcome(self, game)
+        }
+    }
+}
+
+
+// giving check for the third time wins.
+pub struct ThreeCheck;
+
+impl ThreeCheck {
+    pub const CHECKS: u8 = 3;
+}
+
+impl Variant for ThreeCheck {
+    fn name(&self) -> &'static str {
+        "Three-check"
+    }
+
+    fn outcome(&self, game: &Game) -> Option<Outcome> {
+        let mover = !game.turn_white;
+        match game.checks_given(mover) >= ThreeCheck::CHECKS {
+            true => Some(Outcome::ThirdCheck(mover)),
+            false => standard_outcome(self, game)
+        }
+    }
+
+    fn counts_checks(&self) -> bool {
+        true
+    }
+}
+
+
+// captured pieces change sides and can be dropped back onto the board instead of moving.
+pub struct Crazyhouse;
+
+impl Variant for Crazyhouse {
+    fn name(&self) -> &'static str {
+        "Crazyhouse"
+    }
+
+    // it isn't mate or stalemate while a drop can still save the day.
+    fn outcome(&self, game: &Game) -> Option<Outcome> {
+        match can_drop(&game.board, &game.pockets[side(game.turn_white)], game.turn_white) {
+            true => None,
+            false => standard_outcome(self, game)
+        }
+    }
+
+    fn has_pockets(&self) -> bool {
+        true
+    }
+}
+
+
+// every variant that can be picked when starting a game.
+pub const VARIANTS: [&dyn Variant; 13] = [&Standard, &KingOfTheHill, &ThreeCheck, &Crazyhouse, &Atomic, &Antichess, &Horde, &RacingKings, &FogOfWar, &Kriegspiel, &Bughouse, &Capablanca, &Grand];
+
+// looks a variant up by name, ignoring case, spaces and dashes, so "three-check" and "ThreeCheck" both work.
+pub fn find(name: &str) -> Option<&'static dyn Variant> {
+    let simplify = |name: &str| name.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase();
+    VARIANTS.into_iter().find(|variant| simplify(variant.name()) == simplify(name))
+}
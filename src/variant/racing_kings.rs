@@ -0,0 +1,91 @@
+use crate::{
+    board::{Board, Loc, do_move, read_board},
+    check::{self, get_king},
+    fen::from_variant_fen,
+    game::{Game, Outcome},
+    types::{IllegalMove, MoveData, Movement::*, Space::*}
+};
+use super::{Standard, Variant, standard_outcome};
+
+
+// both kings race for the eighth rank, and nobody is allowed to give check.
+pub struct RacingKings;
+
+impl RacingKings {
+    pub const START: &'static str = "8/8/8/8/8/8/krbnNBRK/qrbnNBRQ w - - 0 1";
+}
+
+// true if the king of the given team is on the eighth rank.
+fn home(board: &Board, is_white: bool) -> bool {
+    get_king(board, is_white).is_some_and(|kingc| kingc[1] == 1)
+}
+
+// true if the move would put the other side in check.
+fn gives_check(board: &Board, fromc: Loc, movement: MoveData) -> bool {
+    let from = match read_board(board, fromc) {
+        Some(Open) | None => {return false;},
+        Some(piece) => piece
+    };
+
+    let mut test_board = *board;
+    do_move(&mut test_board, fromc, from, movement.to, movement.relation);
+    check::in_check(&test_board, !from.is_white())
+}
+
+impl Variant for RacingKings {
+    fn name(&self) -> &'static str {
+        "Racing Kings"
+    }
+
+    fn start(&self) -> Option<Board> {
+        from_variant_fen(&RacingKings, RacingKings::START).ok().map(|game| game.board)
+    }
+
+    fn filter(&self, board: &Board, fromc: Loc, moves: &mut Vec<MoveData>) {
+        for movement in moves.iter_mut() {
+            if movement.relation != Blocked && gives_check(board, fromc, *movement) {
+                movement.relation = Check;
+            }
+        }
+    }
+
+    fn check_reason(&self, board: &Board, fromc: Loc, toc: Loc) -> IllegalMove {
+        let from = match read_board(board, fromc) {
+            Some(Open) | None => {return IllegalMove::NoPiece;},
+            Some(piece) => piece
+        };
+
+        // the move would be fine in normal chess, so the check it gives is the problem.
+        let gives = Standard.moves(board, fromc).into_iter()
+            .any(|movement| movement.to == toc && !matches!(movement.relation, Check | Blocked));
+        match gives {
+            true => IllegalMove::GivesCheck,
+            false => check::check_reason(board, fromc, from)
+        }
+    }
+
+    fn castles(&self) -> bool {
+        false
+    }
+
+    // white moves first, so if white gets there black has one more move to catch up and draw.
+    fn outcome(&self, game: &Game) -> Option<Outcome> {
+        let board = &game.board;
+        match (home(board, true), home(board, false)) {
+            (true, true) => Some(Outcome::BothKingsHome),
+            (false, true) => Some(Outcome::KingHome(false)),
+            (true, false) => {
+                let catch_up = match get_king(board, false) {
+                    Some(kingc) if !game.turn_white => self.moves(board, kingc).iter()
+                        .any(|movement| movement.to[1] == 1 && !matches!(movement.relation, Check | Blocked)),
+                    _ => false
+                };
+                match catch_up {
+                    true => None,
+                    false => Some(Outcome::KingHome(true))
+                }
+            },
+            (false, false) => standard_outcome(self, game)
+        }
+    }
+}